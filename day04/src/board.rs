@@ -1,4 +1,5 @@
 use itertools::Itertools;
+use crate::pattern::WinPattern;
 
 #[derive(Debug, Clone)]
 pub struct Cell {
@@ -27,31 +28,27 @@ impl Board {
         &self.data[(y * self.width) + x]
     }
 
-    pub fn mark(&mut self, value: i32) -> bool {
-        let cell = self.data.iter_mut().find(|cell| cell.value == value);
-
-        if let Some(cell) = cell{
-            cell.marked = true;
-            true
-        } else {
-            false
-        }
+    pub fn cells(&self) -> &[Cell] {
+        &self.data
     }
 
-    pub fn winner(&self) -> bool {
-        self.has_full_row() || self.has_full_column()
-    }
+    pub fn mark_cell(&mut self, index: usize) -> bool {
+        let cell = &mut self.data[index];
+        let newly_marked = !cell.marked;
+        cell.marked = true;
 
-    fn has_full_row(&self) -> bool {
-        (0..self.height).any(|y| 
-            (0..self.width).all(|x| self.get(x, y).marked)
-        )
+        newly_marked
     }
 
-    fn has_full_column(&self) -> bool {
-        (0..self.width).any(|x| 
-            (0..self.height).all(|y| self.get(x, y).marked)
-        )
+    // Fails when one of the patterns doesn't fit on the board
+    pub fn winner(&self, patterns: &[WinPattern]) -> Result<bool, String> {
+        for pattern in patterns {
+            if pattern.lines(self.width, self.height)?.iter().any(|line| line.iter().all(|&(x, y)| self.get(x, y).marked)) {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
     }
 
     pub fn sum_unmarked(&self) -> i32 {
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use itertools::Itertools;
+use crate::board::Board;
+use crate::pattern::WinPattern;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    pub draw: usize,
+    pub value: i32,
+    pub board: usize,
+    pub score: i32,
+}
+
+// Winning lines for one board size, shared by every board of that size
+#[derive(Debug, Clone)]
+struct Layout {
+    line_sizes: Vec<usize>,
+    cell_lines: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    fn new(width: usize, height: usize, patterns: &[WinPattern]) -> Result<Layout, String> {
+        let lines = patterns.iter()
+            .map(|pattern| pattern.lines(width, height))
+            .flatten_ok()
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut cell_lines = vec![vec![]; width * height];
+
+        for (line_index, line) in lines.iter().enumerate() {
+            for &(x, y) in line {
+                cell_lines[(y * width) + x].push(line_index);
+            }
+        }
+
+        Ok(Layout {
+            line_sizes: lines.iter().map(|line| line.len()).collect(),
+            cell_lines,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BoardState {
+    board: Board,
+    layout: usize,
+    line_hits: Vec<usize>,
+    won: bool,
+}
+
+impl BoardState {
+    // Returns true when this mark completes one of the winning lines
+    fn mark(&mut self, cell: usize, layout: &Layout) -> bool {
+        if self.won || !self.board.mark_cell(cell) {
+            return false;
+        }
+
+        let mut completed = false;
+        for &line in layout.cell_lines[cell].iter() {
+            self.line_hits[line] += 1;
+            completed |= self.line_hits[line] == layout.line_sizes[line];
+        }
+
+        completed
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BingoEngine {
+    patterns: Vec<WinPattern>,
+    boards: Vec<BoardState>,
+    layouts: Vec<Layout>,
+    index: HashMap<i32, Vec<(usize, usize)>>,
+    draws: usize,
+}
+
+impl BingoEngine {
+    // Fails when one of the patterns doesn't fit on one of the boards
+    pub fn with_patterns(boards: &[Board], patterns: &[WinPattern]) -> Result<BingoEngine, String> {
+        let mut index: HashMap<i32, Vec<(usize, usize)>> = HashMap::new();
+        let mut layout_ids: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut layouts = vec![];
+        let mut states = vec![];
+
+        for (board_index, board) in boards.iter().enumerate() {
+            for (cell_index, cell) in board.cells().iter().enumerate() {
+                index.entry(cell.value).or_default().push((board_index, cell_index));
+            }
+
+            let layout = match layout_ids.get(&(board.width, board.height)) {
+                Some(layout) => *layout,
+                None => {
+                    layouts.push(Layout::new(board.width, board.height, patterns)?);
+                    layout_ids.insert((board.width, board.height), layouts.len() - 1);
+                    layouts.len() - 1
+                }
+            };
+
+            states.push(BoardState {
+                board: board.clone(),
+                layout,
+                line_hits: vec![0; layouts[layout].line_sizes.len()],
+                won: false,
+            });
+        }
+
+        Ok(BingoEngine {
+            patterns: patterns.to_vec(),
+            boards: states,
+            layouts,
+            index,
+            draws: 0,
+        })
+    }
+
+    pub fn draw(&mut self, value: i32) -> Vec<Win> {
+        let draw = self.draws;
+        self.draws += 1;
+
+        let mut wins = vec![];
+        let cells = match self.index.get(&value) {
+            Some(cells) => cells,
+            None => return wins,
+        };
+
+        for &(board_index, cell_index) in cells {
+            let state = &mut self.boards[board_index];
+
+            if state.mark(cell_index, &self.layouts[state.layout]) {
+                debug_assert_eq!(Ok(true), state.board.winner(&self.patterns));
+                state.won = true;
+                wins.push(Win { draw, value, board: board_index, score: state.board.sum_unmarked() * value });
+            }
+        }
+
+        wins
+    }
+
+    pub fn play(&mut self, values: &[i32]) -> Vec<Win> {
+        values.iter().flat_map(|value| self.draw(*value)).collect()
+    }
+}
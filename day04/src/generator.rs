@@ -0,0 +1,46 @@
+use itertools::Itertools;
+use crate::board::Board;
+
+// SplitMix64, so generated inputs stay stable for a given seed
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Generator {
+        Generator { state: seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn shuffled(&mut self, max_value: i32) -> Vec<i32> {
+        let mut values = (0..max_value).collect_vec();
+
+        for i in (1..values.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            values.swap(i, j);
+        }
+
+        values
+    }
+
+    pub fn board(&mut self, width: usize, height: usize, max_value: i32) -> Board {
+        if (max_value as usize) < width * height {
+            panic!("Not enough values to fill a {}x{} board", width, height);
+        }
+
+        let values = self.shuffled(max_value).into_iter().take(width * height).collect_vec();
+        Board::new(width, height, &values)
+    }
+
+    pub fn draws(&mut self, max_value: i32) -> Vec<i32> {
+        self.shuffled(max_value)
+    }
+}
@@ -1,8 +1,16 @@
-use std::fs;
+use std::{env, fs};
 use board::Board;
+use engine::{BingoEngine, Win};
+use generator::Generator;
 use itertools::Itertools;
+use pattern::WinPattern;
+use search::{find_draw_order, Goal};
 
 mod board;
+mod engine;
+mod generator;
+mod pattern;
+mod search;
 
 struct Input {
     values: Vec<i32>,
@@ -10,8 +18,53 @@ struct Input {
 }
 
 fn main() {
-    let input = fs::read_to_string("input2.txt").unwrap();
-    let values = parse_input(&input);
+    let args = env::args().skip(1).collect_vec();
+    let patterns = parse_patterns(&args).unwrap();
+
+    let mut values = match option_value(&args, "--generate") {
+        Some(seed) => {
+            let board_count = option_value(&args, "--boards").map(|count| count.parse().unwrap()).unwrap_or(100);
+            generate_input(seed.parse().unwrap(), board_count)
+        },
+        None => parse_input(&fs::read_to_string("input2.txt").unwrap()),
+    };
+
+    let search = option_value(&args, "--win-first").map(|board| (Goal::First, board))
+        .or_else(|| option_value(&args, "--win-last").map(|board| (Goal::Last, board)));
+
+    if let Some((goal, board)) = search {
+        let order = match find_draw_order(&values.boards, board.parse().unwrap(), goal, &patterns) {
+            Ok(Some(order)) => order,
+            Ok(None) => {
+                println!("No draw order lets this board win as requested");
+                return;
+            }
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+
+        if !order.optimal {
+            eprintln!("Search limit reached, draw order may not be the shortest");
+        }
+        values.values = order.values;
+    }
+
+    if args.iter().any(|arg| arg == "--generate" || arg == "--win-first" || arg == "--win-last") {
+        print!("{}", format_input(&values));
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--log") {
+        match game_log(&values, &patterns) {
+            Ok(log) => for win in log {
+                println!("Draw {} ({}): board {} wins with score {}", win.draw, win.value, win.board, win.score);
+            },
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
 
     let part1 = part1(&values);
     let part2 = part2(&values);
@@ -19,18 +72,58 @@ fn main() {
     println!("Part 1: {} Part 2: {}", part1, part2);
 }
 
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().tuple_windows()
+        .find(|(arg, _)| *arg == flag)
+        .map(|(_, value)| value.as_str())
+}
+
+fn parse_patterns(args: &[String]) -> Result<Vec<WinPattern>, String> {
+    let patterns: Vec<WinPattern> = args.iter()
+        .tuple_windows()
+        .filter(|(flag, _)| *flag == "--rule")
+        .map(|(_, rule)| WinPattern::parse(rule))
+        .try_collect()?;
+
+    if patterns.is_empty() {
+        Ok(WinPattern::standard())
+    } else {
+        Ok(patterns)
+    }
+}
+
+fn generate_input(seed: u64, board_count: usize) -> Input {
+    let mut generator = Generator::new(seed);
+
+    Input {
+        boards: (0..board_count).map(|_| generator.board(5, 5, 100)).collect(),
+        values: generator.draws(100),
+    }
+}
+
+fn format_input(input: &Input) -> String {
+    let boards = input.boards.iter().map(|board| {
+        (0..board.height).map(|y| {
+            (0..board.width).map(|x| format!("{:>2}", board.get(x, y).value)).join(" ")
+        }).join("\n")
+    }).join("\n\n");
+
+    format!("{}\n\n{}\n", input.values.iter().join(","), boards)
+}
+
 fn parse_input(input: &str) -> Input {
-    let parts = input.split("\r\n\r\n").collect_vec();
+    let input = input.replace("\r\n", "\n");
+    let parts = input.split("\n\n").collect_vec();
 
     Input { 
         values: parts[0].split(',').map(|v| v.parse().unwrap()).collect_vec(), 
-        boards: parts[1..].iter().map(|part| parse_board(*part)).collect()
+        boards: parts[1..].iter().map(|part| parse_board(part)).collect()
      }
 }
 
 fn parse_board(input: &str) -> Board {
     let lines = input.lines().collect_vec();
-    let values: Vec<i32> = input.lines().map(parse_board_line).flatten().collect();
+    let values: Vec<i32> = input.lines().flat_map(parse_board_line).collect();
 
     Board::new(parse_board_line(lines[0]).len(), lines.len(), &values)
 }
@@ -48,16 +141,132 @@ fn part2(input: &Input) -> i32 {
 }
 
 fn compute_win_scores(input: &Input) -> Vec<i32> {
-    let mut boards = input.boards.clone();
-    let mut wins= vec![];
+    game_log(input, &WinPattern::standard()).unwrap().iter()
+        .map(|win| win.score)
+        .collect()
+}
 
-    for value in input.values.iter() {
-        for board in boards.iter_mut() {
-            if !board.winner() && board.mark(*value) && board.winner() {
-                wins.push(board.sum_unmarked() * value);
-            }
-        }
+fn game_log(input: &Input, patterns: &[WinPattern]) -> Result<Vec<Win>, String> {
+    Ok(BingoEngine::with_patterns(&input.boards, patterns)?.play(&input.values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_should_work() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part1(&values);
+
+        assert_eq!(4512, result);
+    }
+
+    #[test]
+    fn part2_should_work() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part2(&values);
+
+        assert_eq!(1924, result);
+    }
+
+    #[test]
+    fn engine_should_report_each_board_once() {
+        let boards = vec![
+            Board::new(2, 2, &[1, 2, 3, 4]),
+            Board::new(2, 2, &[5, 6, 7, 1]),
+        ];
+        let mut engine = BingoEngine::with_patterns(&boards, &WinPattern::standard()).unwrap();
+        let wins = engine.play(&[1, 2, 1, 6, 5, 3]);
+
+        assert_eq!(vec![
+            Win { draw: 1, value: 2, board: 0, score: 14 },
+            Win { draw: 3, value: 6, board: 1, score: 72 },
+        ], wins);
+    }
+
+    #[test]
+    fn game_log_should_support_diagonals_and_corners() {
+        let input = Input {
+            values: vec![1, 5, 3, 7, 9],
+            boards: vec![Board::new(3, 3, &[1, 2, 3, 4, 5, 6, 7, 8, 9])],
+        };
+
+        let diagonal = game_log(&input, &[WinPattern::Diagonals]).unwrap();
+        let corners = game_log(&input, &[WinPattern::FourCorners]).unwrap();
+
+        assert_eq!(vec![Win { draw: 3, value: 7, board: 0, score: 203 }], diagonal);
+        assert_eq!(vec![Win { draw: 4, value: 9, board: 0, score: 180 }], corners);
+    }
+
+    #[test]
+    fn game_log_should_support_blackout_and_masks() {
+        let input = Input {
+            values: vec![4, 1, 3, 2],
+            boards: vec![Board::new(2, 2, &[1, 2, 3, 4])],
+        };
+
+        let blackout = game_log(&input, &[WinPattern::Blackout]).unwrap();
+        let mask = game_log(&input, &[WinPattern::parse("mask:#./.#").unwrap()]).unwrap();
+
+        assert_eq!(vec![Win { draw: 3, value: 2, board: 0, score: 0 }], blackout);
+        assert_eq!(vec![Win { draw: 1, value: 1, board: 0, score: 5 }], mask);
+    }
+
+    #[test]
+    fn patterns_that_dont_fit_should_be_rejected() {
+        let input = Input {
+            values: vec![1, 2, 3],
+            boards: vec![Board::new(3, 3, &[1, 2, 3, 4, 5, 6, 7, 8, 9]), Board::new(3, 2, &[1, 2, 3, 4, 5, 6])],
+        };
+
+        assert_eq!(Err(String::from("Mask is 6x1, which doesn't fit on a 3x3 board")),
+            game_log(&input, &[WinPattern::parse("mask:######").unwrap()]));
+        assert_eq!(Err(String::from("Diagonals need a square board, but the board is 3x2")),
+            game_log(&input, &[WinPattern::Rows, WinPattern::Diagonals]));
+        assert!(find_draw_order(&input.boards, 0, Goal::First, &[WinPattern::Diagonals]).is_err());
+        assert_eq!(Ok(false), input.boards[0].winner(&[WinPattern::Diagonals]));
+        assert!(input.boards[1].winner(&[WinPattern::Diagonals]).is_err());
+    }
+
+    #[test]
+    fn generated_input_should_round_trip() {
+        let input = generate_input(7, 3);
+        let parsed = parse_input(&format_input(&input));
+
+        assert_eq!(input.values, parsed.values);
+        assert_eq!(input.boards.iter().map(|b| b.cells().iter().map(|c| c.value).collect_vec()).collect_vec(),
+            parsed.boards.iter().map(|b| b.cells().iter().map(|c| c.value).collect_vec()).collect_vec());
     }
 
-    wins
+    #[test]
+    fn search_should_find_shortest_first_and_last_wins() {
+        let mut input = generate_input(42, 4);
+        let patterns = WinPattern::standard();
+
+        input.values = find_draw_order(&input.boards, 2, Goal::First, &patterns).unwrap().unwrap().values;
+        let first = game_log(&input, &patterns).unwrap();
+        assert_eq!(2, first[0].board);
+        assert_eq!(5, input.values.len());
+
+        let order = find_draw_order(&input.boards, 2, Goal::Last, &patterns).unwrap().unwrap();
+        assert!(order.optimal);
+
+        input.values = order.values;
+        let last = game_log(&input, &patterns).unwrap();
+        assert_eq!(4, last.len());
+        assert_eq!(2, last[3].board);
+        assert_eq!(input.values.len() - 1, last[3].draw);
+    }
+
+    #[test]
+    fn search_should_report_impossible_targets() {
+        let boards = vec![Board::new(2, 2, &[1, 2, 3, 4]), Board::new(2, 2, &[1, 2, 3, 4])];
+        let patterns = WinPattern::standard();
+
+        assert!(find_draw_order(&boards, 1, Goal::First, &patterns).unwrap().is_none());
+        assert_eq!(Err(String::from("There is no board 2")), find_draw_order(&boards, 2, Goal::Last, &patterns).map(|o| o.is_some()));
+    }
 }
\ No newline at end of file
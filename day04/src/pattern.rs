@@ -0,0 +1,88 @@
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WinPattern {
+    Rows,
+    Columns,
+    Diagonals,
+    FourCorners,
+    Blackout,
+    Custom(Vec<(usize, usize)>),
+}
+
+impl WinPattern {
+    pub fn standard() -> Vec<WinPattern> {
+        vec![WinPattern::Rows, WinPattern::Columns]
+    }
+
+    pub fn parse(input: &str) -> Result<WinPattern, String> {
+        match input {
+            "rows" => Ok(WinPattern::Rows),
+            "columns" => Ok(WinPattern::Columns),
+            "diagonals" => Ok(WinPattern::Diagonals),
+            "corners" => Ok(WinPattern::FourCorners),
+            "blackout" => Ok(WinPattern::Blackout),
+            _ => match input.strip_prefix("mask:") {
+                Some(mask) => parse_mask(mask),
+                None => Err(format!("Unknown win pattern {}", input)),
+            }
+        }
+    }
+
+    // Every set of cells that wins the board when fully marked, or an error when the pattern
+    // can't be placed on a board of this size
+    pub fn lines(&self, width: usize, height: usize) -> Result<Vec<Vec<(usize, usize)>>, String> {
+        let lines = match self {
+            WinPattern::Rows => (0..height)
+                .map(|y| (0..width).map(|x| (x, y)).collect_vec())
+                .collect(),
+            WinPattern::Columns => (0..width)
+                .map(|x| (0..height).map(|y| (x, y)).collect_vec())
+                .collect(),
+            WinPattern::Diagonals if width == height => vec![
+                (0..width).map(|i| (i, i)).collect_vec(),
+                (0..width).map(|i| (width - 1 - i, i)).collect_vec(),
+            ],
+            WinPattern::Diagonals => return Err(format!("Diagonals need a square board, but the board is {}x{}", width, height)),
+            WinPattern::FourCorners => vec![
+                vec![(0, 0), (width - 1, 0), (0, height - 1), (width - 1, height - 1)]
+                    .into_iter().unique().collect_vec()
+            ],
+            WinPattern::Blackout => vec![
+                (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).collect_vec()
+            ],
+            WinPattern::Custom(cells) if cells.iter().all(|&(x, y)| x < width && y < height) => vec![
+                cells.iter().copied().unique().collect_vec()
+            ],
+            WinPattern::Custom(cells) => {
+                let mask_width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+                let mask_height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+
+                return Err(format!("Mask is {}x{}, which doesn't fit on a {}x{} board", mask_width, mask_height, width, height));
+            },
+        };
+
+        Ok(lines)
+    }
+}
+
+// Masks are written as rows separated by '/', with '#' for cells that must be marked
+fn parse_mask(mask: &str) -> Result<WinPattern, String> {
+    let mut cells = vec![];
+
+    for (y, row) in mask.split('/').enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '#' => cells.push((x, y)),
+                '.' => {},
+                _ => return Err(format!("Unexpected character {} in mask", c)),
+            }
+        }
+    }
+
+    if cells.is_empty() {
+        return Err(String::from("Mask does not contain any cells"));
+    }
+
+    Ok(WinPattern::Custom(cells))
+}
@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use itertools::Itertools;
+use crate::board::Board;
+use crate::pattern::WinPattern;
+
+const NODE_LIMIT: usize = 200_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrawOrder {
+    pub values: Vec<i32>,
+    // False when the search gave up before proving no shorter order exists
+    pub optimal: bool,
+}
+
+// Finds the shortest draw order after which `target` is the first (or last) board to win.
+// Fails when there is no such board or one of the patterns doesn't fit on one of the boards.
+pub fn find_draw_order(boards: &[Board], target: usize, goal: Goal, patterns: &[WinPattern]) -> Result<Option<DrawOrder>, String> {
+    if target >= boards.len() {
+        return Err(format!("There is no board {}", target));
+    }
+
+    let lines: Vec<Vec<HashSet<i32>>> = boards.iter().map(|board| winning_sets(board, patterns)).try_collect()?;
+
+    Ok(match goal {
+        Goal::First => find_first(&lines, target).map(|values| DrawOrder { values, optimal: true }),
+        Goal::Last => find_last(&lines, target),
+    })
+}
+
+fn winning_sets(board: &Board, patterns: &[WinPattern]) -> Result<Vec<HashSet<i32>>, String> {
+    let lines: Vec<Vec<(usize, usize)>> = patterns.iter()
+        .map(|pattern| pattern.lines(board.width, board.height))
+        .flatten_ok()
+        .try_collect()?;
+
+    Ok(lines.iter()
+        .map(|line| line.iter().map(|&(x, y)| board.get(x, y).value).collect::<HashSet<i32>>())
+        .unique_by(|set| set.iter().copied().sorted().collect_vec())
+        .collect())
+}
+
+fn has_line_within(lines: &[HashSet<i32>], drawn: &HashSet<i32>) -> bool {
+    lines.iter().any(|line| line.is_subset(drawn))
+}
+
+// Drawing exactly one of the target's lines is optimal; any other board completing
+// a line within those values would win on or before the final draw
+fn find_first(lines: &[Vec<HashSet<i32>>], target: usize) -> Option<Vec<i32>> {
+    lines[target].iter()
+        .filter(|line| lines.iter().enumerate()
+            .all(|(index, other)| index == target || !has_line_within(other, line)))
+        .min_by_key(|line| line.len())
+        .map(|line| line.iter().copied().sorted().collect())
+}
+
+// Every other board needs a completed line while the target still has none, after
+// which the remainder of one target line is drawn. Branch and bound over the line
+// choice per board, always branching on the board furthest from winning, minimising
+// the number of distinct values drawn. This is exponential in the worst case, so after
+// NODE_LIMIT nodes the best order found so far is returned.
+fn find_last(lines: &[Vec<HashSet<i32>>], target: usize) -> Option<DrawOrder> {
+    if lines[target].is_empty() {
+        return None;
+    }
+
+    let others = (0..lines.len()).filter(|index| *index != target).collect_vec();
+    let mut search = LastSearch { lines, target, best: None, nodes: 0 };
+    search.search(&others, &HashSet::new());
+
+    let optimal = search.nodes <= NODE_LIMIT;
+    search.best.map(|(drawn, final_line)| DrawOrder {
+        values: drawn.iter().copied().sorted()
+            .chain(final_line.difference(&drawn).copied().sorted())
+            .collect(),
+        optimal,
+    })
+}
+
+struct LastSearch<'a> {
+    lines: &'a [Vec<HashSet<i32>>],
+    target: usize,
+    best: Option<(HashSet<i32>, HashSet<i32>)>,
+    nodes: usize,
+}
+
+impl<'a> LastSearch<'a> {
+    fn best_len(&self) -> usize {
+        self.best.as_ref().map(|(drawn, line)| drawn.union(line).count()).unwrap_or(usize::MAX)
+    }
+
+    fn missing(&self, board: usize, drawn: &HashSet<i32>) -> Option<usize> {
+        self.lines[board].iter().map(|line| line.difference(drawn).count()).min()
+    }
+
+    fn search(&mut self, pending: &[usize], drawn: &HashSet<i32>) {
+        self.nodes += 1;
+        if self.nodes > NODE_LIMIT && self.best.is_some() {
+            return;
+        }
+
+        if has_line_within(&self.lines[self.target], drawn) {
+            return;
+        }
+
+        let mut missing = vec![];
+        for &board in pending {
+            match self.missing(board, drawn) {
+                Some(0) => {},
+                Some(count) => missing.push((board, count)),
+                None => return,
+            }
+        }
+
+        let remaining = missing.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        if drawn.len() + remaining + 1 >= self.best_len() {
+            return;
+        }
+
+        let next = missing.iter()
+            .max_by_key(|(board, count)| (*count, usize::MAX - self.lines[*board].len()))
+            .map(|(board, _)| *board);
+
+        let board = match next {
+            Some(board) => board,
+            None => {
+                let final_line = self.lines[self.target].iter()
+                    .min_by_key(|line| line.difference(drawn).count())
+                    .unwrap();
+
+                if drawn.union(final_line).count() < self.best_len() {
+                    self.best = Some((drawn.clone(), final_line.clone()));
+                }
+                return;
+            }
+        };
+
+        let pending = missing.iter().map(|(index, _)| *index).filter(|index| *index != board).collect_vec();
+        let options = self.lines[board].iter()
+            .sorted_by_key(|line| line.difference(drawn).count())
+            .collect_vec();
+
+        for line in options {
+            let next: HashSet<i32> = drawn.union(line).copied().collect();
+            self.search(&pending, &next);
+        }
+    }
+}
@@ -1,5 +1,10 @@
-use std::{fs, collections::HashMap};
+use std::{env, fs, collections::HashMap, path::Path};
 use itertools::Itertools;
+use render::{ascii_heatmap, write_image};
+use sweep::count_overlaps;
+
+mod render;
+mod sweep;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Point {
@@ -10,6 +15,29 @@ pub struct Point {
 fn main() {
     let input = fs::read_to_string("input2.txt").unwrap();
     let values = parse_input(&input);
+    let args = env::args().skip(1).collect_vec();
+    let slopes = if args.iter().any(|arg| arg == "--any-slope") { Slopes::Include } else { Slopes::Reject };
+
+    if args.iter().any(|arg| arg == "--overlaps") {
+        let lines = select_lines(&values, true, slopes).unwrap();
+
+        for (point, count) in overlapping_cells(&render_lines(&lines)) {
+            println!("{},{}: {}", point.x, point.y, count);
+        }
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--heatmap") {
+        let lines = select_lines(&values, true, slopes).unwrap();
+        print!("{}", ascii_heatmap(&render_lines(&lines), 120, 60));
+        return;
+    }
+
+    if let Some((_, path)) = args.iter().tuple_windows().find(|(arg, _)| *arg == "--image") {
+        let lines = select_lines(&values, true, slopes).unwrap();
+        write_image(&render_lines(&lines), Path::new(path)).unwrap();
+        return;
+    }
 
     let part1 = part1(&values);
     let part2 = part2(&values);
@@ -31,33 +59,82 @@ fn parse_coordinate(coordinate: &str) -> Point {
     Point { x: parts.0.parse().unwrap(), y: parts.1.parse().unwrap() }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slopes {
+    // Lines that are not horizontal, vertical or at exactly 45 degrees are an error
+    Reject,
+    // Lines of any slope are rasterised with Bresenham's algorithm
+    Include,
+}
+
 fn part1(values: &[(Point, Point)]) -> usize {
-    count_duplicate_cells(&render_lines(values, false))
+    count_cells(values, false, Slopes::Reject).unwrap()
 }
 
 fn part2(values: &[(Point, Point)]) -> usize {
-    count_duplicate_cells(&render_lines(values, true))
+    count_cells(values, true, Slopes::Reject).unwrap()
 }
 
-fn render_lines(lines: &[(Point, Point)], draw_diagonals: bool) -> HashMap<Point, usize> {
+fn count_cells(values: &[(Point, Point)], draw_diagonals: bool, slopes: Slopes) -> Result<usize, String> {
+    let lines = select_lines(values, draw_diagonals, slopes)?;
+
+    if lines.iter().all(|line| !is_diagonal(&line.0, &line.1) || is_45_degrees(&line.0, &line.1)) {
+        Ok(count_overlaps(&lines))
+    } else {
+        Ok(count_duplicate_cells(&render_lines(&lines)))
+    }
+}
+
+fn select_lines(lines: &[(Point, Point)], draw_diagonals: bool, slopes: Slopes) -> Result<Vec<(Point, Point)>, String> {
+    let lines = lines.iter()
+        .filter(|line| !is_diagonal(&line.0, &line.1) || draw_diagonals)
+        .cloned()
+        .collect_vec();
+
+    if slopes == Slopes::Reject {
+        if let Some(line) = lines.iter().find(|line| is_diagonal(&line.0, &line.1) && !is_45_degrees(&line.0, &line.1)) {
+            return Err(format!("Line {},{} -> {},{} is not at 45 degrees", line.0.x, line.0.y, line.1.x, line.1.y));
+        }
+    }
+
+    Ok(lines)
+}
+
+fn render_lines(lines: &[(Point, Point)]) -> HashMap<Point, usize> {
     lines.iter()
-        .filter(|line| !is_diagonal(&line.0, &line.1) || (is_diagonal(&line.0, &line.1) && draw_diagonals))
-        .map(|line| project_line(&line.0, &line.1))
-        .flatten()
-        .counts_by(|point| point)
+        .flat_map(|line| project_line(&line.0, &line.1))
+        .counts()
 }
 
+// Bresenham's line algorithm, always walking from the smallest endpoint so that
+// a line covers the same cells regardless of the direction it was given in
 pub fn project_line(p1: &Point, p2: &Point) -> Vec<Point> {
-    let vx = (p2.x - p1.x).signum();
-    let vy = (p2.y - p1.y).signum();
-    let steps = (p2.x - p1.x).abs().max((p2.y - p1.y).abs());
+    let (from, to) = if (p1.x, p1.y) <= (p2.x, p2.y) { (p1, p2) } else { (p2, p1) };
+    let dx = (to.x - from.x).abs();
+    let dy = -(to.y - from.y).abs();
+    let sx = (to.x - from.x).signum();
+    let sy = (to.y - from.y).signum();
 
-    let mut point = p1.clone();
+    let mut point = from.clone();
+    let mut error = dx + dy;
     let mut points = Vec::new();
 
-    for _ in 0..steps+1 {
+    loop {
         points.push(point.clone());
-        point = Point { x: point.x + vx, y: point.y + vy};
+
+        if point == *to {
+            break;
+        }
+
+        let e2 = error * 2;
+        if e2 >= dy {
+            error += dy;
+            point.x += sx;
+        }
+        if e2 <= dx {
+            error += dx;
+            point.y += sy;
+        }
     }
 
     points
@@ -67,6 +144,137 @@ fn count_duplicate_cells(cells: &HashMap<Point, usize>) -> usize {
     cells.values().filter(|count| **count > 1).count()
 }
 
+fn overlapping_cells(cells: &HashMap<Point, usize>) -> Vec<(Point, usize)> {
+    cells.iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(point, count)| (point.clone(), *count))
+        .sorted_by_key(|(point, _)| (point.y, point.x))
+        .collect()
+}
+
 fn is_diagonal(p1: &Point, p2: &Point) -> bool {
     p1.x != p2.x && p1.y != p2.y
+}
+
+fn is_45_degrees(p1: &Point, p2: &Point) -> bool {
+    (p2.x - p1.x).abs() == (p2.y - p1.y).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_should_work() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part1(&values);
+
+        assert_eq!(5, result);
+    }
+
+    #[test]
+    fn part2_should_work() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part2(&values);
+
+        assert_eq!(12, result);
+    }
+
+    #[test]
+    fn count_overlaps_should_match_rendered_lines() {
+        let input = fs::read_to_string("input.txt").unwrap();
+        let values = parse_input(&input);
+
+        for draw_diagonals in [false, true] {
+            let lines = select_lines(&values, draw_diagonals, Slopes::Reject).unwrap();
+            assert_eq!(count_duplicate_cells(&render_lines(&lines)), count_overlaps(&lines));
+        }
+    }
+
+    #[test]
+    fn count_overlaps_should_only_cross_spanned_keys() {
+        let point = |x, y| Point { x, y };
+        let horizontal = (0..200).map(|i| (point(0, i * 5), point(995, i * 5)));
+        let vertical = (0..200).map(|i| (point(i * 5, 0), point(i * 5, 995)));
+        let apart = (0..200).map(|i| (point(2000 + i, 5000), point(2000 + i, 5001)));
+        let lines = horizontal.chain(vertical).chain(apart).collect_vec();
+
+        assert_eq!(40000, count_overlaps(&lines));
+    }
+
+    #[test]
+    fn count_overlaps_should_handle_large_coordinates() {
+        let values = parse_input("0,0 -> 4000000,4000000
+4000000,0 -> 0,4000000
+0,2000000 -> 4000000,2000000
+1000000,2000000 -> 3000000,2000000
+2000000,0 -> 2000000,4000000");
+
+        assert_eq!(2000001, count_overlaps(&values));
+    }
+
+    #[test]
+    fn project_line_should_rasterise_any_slope() {
+        let points = project_line(&Point { x: 4, y: 2 }, &Point { x: 0, y: 0 });
+
+        assert_eq!(vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)], points.iter().map(|p| (p.x, p.y)).collect_vec());
+    }
+
+    #[test]
+    fn non_45_degree_lines_should_be_rejected_or_included() {
+        let values = parse_input("0,0 -> 4,2\n0,1 -> 4,1\n2,0 -> 2,4");
+
+        assert!(count_cells(&values, true, Slopes::Reject).is_err());
+        assert_eq!(2, count_cells(&values, true, Slopes::Include).unwrap());
+        assert_eq!(1, count_cells(&values, false, Slopes::Reject).unwrap());
+    }
+
+    #[test]
+    fn overlapping_cells_should_report_multiplicities() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let lines = select_lines(&values, false, Slopes::Reject).unwrap();
+        let cells = overlapping_cells(&render_lines(&lines));
+
+        assert_eq!(vec![(3, 4, 2), (7, 4, 2), (0, 9, 2), (1, 9, 2), (2, 9, 2)],
+            cells.iter().map(|(p, count)| (p.x, p.y, *count)).collect_vec());
+    }
+
+    #[test]
+    fn ascii_heatmap_should_show_overlap_counts() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let lines = select_lines(&values, true, Slopes::Reject).unwrap();
+        let result = ascii_heatmap(&render_lines(&lines), 120, 60);
+
+        assert_eq!("1.1....11.
+.111...2..
+..2.1.111.
+...1.2.2..
+.112313211
+...1.2....
+..1...1...
+.1.....1..
+1.......1.
+222111....
+", result);
+    }
+
+    #[test]
+    fn images_should_cover_the_density_map() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let lines = select_lines(&values, true, Slopes::Reject).unwrap();
+        let cells = render_lines(&lines);
+
+        let ppm = render::to_ppm(&cells);
+        let pgm = render::to_pgm(&cells);
+
+        assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+        assert_eq!(13 + 300, ppm.len());
+        assert!(pgm.starts_with(b"P5\n10 10\n255\n"));
+        assert_eq!(13 + 100, pgm.len());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,111 @@
+use std::{collections::HashMap, fs, io, path::Path};
+use crate::Point;
+
+struct Bounds {
+    min_x: i32,
+    min_y: i32,
+    width: usize,
+    height: usize,
+}
+
+impl Bounds {
+    fn of(cells: &HashMap<Point, usize>) -> Bounds {
+        let min_x = cells.keys().map(|p| p.x).min().unwrap_or(0);
+        let min_y = cells.keys().map(|p| p.y).min().unwrap_or(0);
+        let max_x = cells.keys().map(|p| p.x).max().unwrap_or(0);
+        let max_y = cells.keys().map(|p| p.y).max().unwrap_or(0);
+
+        Bounds {
+            min_x,
+            min_y,
+            width: (max_x - min_x) as usize + 1,
+            height: (max_y - min_y) as usize + 1,
+        }
+    }
+
+    fn count(&self, cells: &HashMap<Point, usize>, x: usize, y: usize) -> usize {
+        let point = Point { x: self.min_x + x as i32, y: self.min_y + y as i32 };
+        cells.get(&point).copied().unwrap_or(0)
+    }
+}
+
+fn colour(count: usize) -> [u8; 3] {
+    match count {
+        0 => [0, 0, 0],
+        1 => [40, 70, 160],
+        2 => [240, 200, 40],
+        3 => [240, 120, 20],
+        _ => [220, 20, 20],
+    }
+}
+
+fn grey(count: usize, max_count: usize) -> u8 {
+    ((count * 255) / max_count.max(1)) as u8
+}
+
+pub fn to_ppm(cells: &HashMap<Point, usize>) -> Vec<u8> {
+    let bounds = Bounds::of(cells);
+    let mut data = format!("P6\n{} {}\n255\n", bounds.width, bounds.height).into_bytes();
+
+    for y in 0..bounds.height {
+        for x in 0..bounds.width {
+            data.extend_from_slice(&colour(bounds.count(cells, x, y)));
+        }
+    }
+
+    data
+}
+
+pub fn to_pgm(cells: &HashMap<Point, usize>) -> Vec<u8> {
+    let bounds = Bounds::of(cells);
+    let max_count = cells.values().copied().max().unwrap_or(0);
+    let mut data = format!("P5\n{} {}\n255\n", bounds.width, bounds.height).into_bytes();
+
+    for y in 0..bounds.height {
+        for x in 0..bounds.width {
+            data.push(grey(bounds.count(cells, x, y), max_count));
+        }
+    }
+
+    data
+}
+
+// Writes a PGM when the path ends in .pgm and a PPM otherwise
+pub fn write_image(cells: &HashMap<Point, usize>, path: &Path) -> io::Result<()> {
+    let data = match path.extension().and_then(|e| e.to_str()) {
+        Some("pgm") => to_pgm(cells),
+        _ => to_ppm(cells),
+    };
+
+    fs::write(path, data)
+}
+
+// Each character covers a square block of cells and shows the highest count in it,
+// with blocks scaled down until the map fits in max_width by max_height characters
+pub fn ascii_heatmap(cells: &HashMap<Point, usize>, max_width: usize, max_height: usize) -> String {
+    let bounds = Bounds::of(cells);
+    let scale = bounds.width.div_ceil(max_width)
+        .max(bounds.height.div_ceil(max_height))
+        .max(1);
+
+    let mut chars = Vec::new();
+    for block_y in (0..bounds.height).step_by(scale) {
+        for block_x in (0..bounds.width).step_by(scale) {
+            let count = (block_y..(block_y + scale).min(bounds.height))
+                .flat_map(|y| (block_x..(block_x + scale).min(bounds.width)).map(move |x| (x, y)))
+                .map(|(x, y)| bounds.count(cells, x, y))
+                .max()
+                .unwrap_or(0);
+
+            chars.push(match count {
+                0 => '.',
+                1..=9 => char::from_digit(count as u32, 10).unwrap(),
+                _ => '+',
+            });
+        }
+
+        chars.push('\n');
+    }
+
+    chars.iter().collect()
+}
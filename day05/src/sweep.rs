@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use itertools::Itertools;
+use crate::Point;
+
+// Every supported line lies on a line a*x + b*y = key for one of these families
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Family {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+impl Family {
+    fn of(p1: &Point, p2: &Point) -> Family {
+        let dx = (p2.x - p1.x) as i64;
+        let dy = (p2.y - p1.y) as i64;
+
+        if dy == 0 {
+            Family::Horizontal
+        } else if dx == 0 {
+            Family::Vertical
+        } else if dx == dy {
+            Family::Diagonal
+        } else if dx == -dy {
+            Family::AntiDiagonal
+        } else {
+            panic!("Line {:?} -> {:?} is not horizontal, vertical or diagonal", p1, p2);
+        }
+    }
+
+    fn coefficients(&self) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Diagonal => (1, -1),
+            Family::AntiDiagonal => (1, 1),
+        }
+    }
+
+    fn key(&self, x: i64, y: i64) -> i64 {
+        let (a, b) = self.coefficients();
+        (a * x) + (b * y)
+    }
+
+    fn position(&self, x: i64, y: i64) -> i64 {
+        match self {
+            Family::Vertical => y,
+            _ => x,
+        }
+    }
+
+    // The cell at a position along the line with the given key
+    fn point(&self, key: i64, position: i64) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (position, key),
+            Family::Vertical => (key, position),
+            Family::Diagonal => (position, position - key),
+            Family::AntiDiagonal => (position, key - position),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Coverage {
+    once: Vec<(i64, i64)>,
+    twice: Vec<(i64, i64)>,
+}
+
+impl Coverage {
+    fn new(mut intervals: Vec<(i64, i64)>) -> Coverage {
+        let mut events = intervals.drain(..)
+            .flat_map(|(start, end)| vec![(start, 1), (end + 1, -1)])
+            .sorted()
+            .peekable();
+
+        let mut coverage = Coverage::default();
+        let mut count = 0;
+
+        while let Some((position, delta)) = events.next() {
+            count += delta;
+
+            if let Some((next, _)) = events.peek() {
+                if *next > position {
+                    if count >= 1 {
+                        extend(&mut coverage.once, position, next - 1);
+                    }
+                    if count >= 2 {
+                        extend(&mut coverage.twice, position, next - 1);
+                    }
+                }
+            }
+        }
+
+        coverage
+    }
+}
+
+fn extend(intervals: &mut Vec<(i64, i64)>, start: i64, end: i64) {
+    match intervals.last_mut() {
+        Some(last) if last.1 + 1 == start => last.1 = end,
+        _ => intervals.push((start, end)),
+    }
+}
+
+fn contains(intervals: &[(i64, i64)], position: i64) -> bool {
+    let index = intervals.partition_point(|(start, _)| *start <= position);
+    index > 0 && intervals[index - 1].1 >= position
+}
+
+fn intersection(f1: Family, key1: i64, f2: Family, key2: i64) -> Option<(i64, i64)> {
+    let (a1, b1) = f1.coefficients();
+    let (a2, b2) = f2.coefficients();
+    let det = (a1 * b2) - (a2 * b1);
+    let x = (key1 * b2) - (key2 * b1);
+    let y = (a1 * key2) - (a2 * key1);
+
+    if det == 0 || x % det != 0 || y % det != 0 {
+        None
+    } else {
+        Some((x / det, y / det))
+    }
+}
+
+// Counts the cells covered by at least two lines without visiting every covered cell.
+// Lines on the same family and key are merged with a sweep over their endpoints, which
+// leaves only single-cell crossings between different families to be resolved. Each covered
+// interval only looks up the keys of the other family that it actually spans.
+pub fn count_overlaps(lines: &[(Point, Point)]) -> usize {
+    let mut intervals: HashMap<Family, HashMap<i64, Vec<(i64, i64)>>> = HashMap::new();
+
+    for (p1, p2) in lines {
+        let family = Family::of(p1, p2);
+        let (x1, y1, x2, y2) = (p1.x as i64, p1.y as i64, p2.x as i64, p2.y as i64);
+        let (start, end) = (family.position(x1, y1), family.position(x2, y2));
+
+        intervals.entry(family).or_default()
+            .entry(family.key(x1, y1)).or_default()
+            .push((start.min(end), start.max(end)));
+    }
+
+    let coverage: HashMap<Family, BTreeMap<i64, Coverage>> = intervals.into_iter()
+        .map(|(family, keys)| (family, keys.into_iter().map(|(key, i)| (key, Coverage::new(i))).collect()))
+        .collect();
+
+    let mut total: i64 = coverage.values()
+        .flat_map(|keys| keys.values())
+        .flat_map(|c| c.twice.iter())
+        .map(|(start, end)| end - start + 1)
+        .sum();
+
+    let mut crossings = HashSet::new();
+    for ((f1, keys1), (f2, keys2)) in coverage.iter().tuple_combinations() {
+        for (key1, c1) in keys1.iter() {
+            for (start, end) in c1.once.iter() {
+                let (x1, y1) = f1.point(*key1, *start);
+                let (x2, y2) = f1.point(*key1, *end);
+                let (k1, k2) = (f2.key(x1, y1), f2.key(x2, y2));
+
+                for (key2, c2) in keys2.range(k1.min(k2)..=k1.max(k2)) {
+                    if let Some((x, y)) = intersection(*f1, *key1, *f2, *key2) {
+                        if contains(&c1.once, f1.position(x, y)) && contains(&c2.once, f2.position(x, y)) {
+                            crossings.insert((x, y));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for (x, y) in crossings {
+        let mut once = 0;
+        let mut twice = 0;
+
+        for (family, keys) in coverage.iter() {
+            if let Some(c) = keys.get(&family.key(x, y)) {
+                let position = family.position(x, y);
+                once += contains(&c.once, position) as i64;
+                twice += contains(&c.twice, position) as i64;
+            }
+        }
+
+        if twice > 0 {
+            total -= twice - 1;
+        } else if once >= 2 {
+            total += 1;
+        }
+    }
+
+    total as usize
+}
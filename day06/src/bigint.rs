@@ -0,0 +1,100 @@
+use std::fmt;
+
+// Minimal arbitrary precision unsigned integer, little endian base 2^32 limbs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn from_u64(value: u64) -> BigUint {
+        let mut result = BigUint { limbs: vec![value as u32, (value >> 32) as u32] };
+        result.normalize();
+        result
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0_u64;
+
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let sum = *self.limbs.get(i).unwrap_or(&0) as u64 + *other.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+
+        limbs.push(carry as u32);
+        let mut result = BigUint { limbs };
+        result.normalize();
+        result
+    }
+
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0_u32; self.limbs.len() + other.limbs.len()];
+
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0_u64;
+
+            for (j, b) in other.limbs.iter().enumerate() {
+                let product = (*a as u64 * *b as u64) + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+
+        let mut result = BigUint { limbs };
+        result.normalize();
+        result
+    }
+
+    pub fn divide_small(&self, divisor: u32) -> (BigUint, u32) {
+        let mut limbs = vec![0_u32; self.limbs.len()];
+        let mut remainder = 0_u64;
+
+        for i in (0..self.limbs.len()).rev() {
+            let value = (remainder << 32) | self.limbs[i] as u64;
+            limbs[i] = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+
+        let mut result = BigUint { limbs };
+        result.normalize();
+        (result, remainder as u32)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut chunks = vec![];
+        let mut value = self.clone();
+
+        while !value.is_zero() {
+            let (quotient, remainder) = value.divide_small(1_000_000_000);
+            chunks.push(remainder);
+            value = quotient;
+        }
+
+        write!(f, "{}", chunks.last().unwrap())?;
+        for chunk in chunks.iter().rev().skip(1) {
+            write!(f, "{:09}", chunk)?;
+        }
+
+        Ok(())
+    }
+}
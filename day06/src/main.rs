@@ -1,8 +1,41 @@
-use std::fs;
+use std::{convert::TryFrom, env, fs};
+use matrix::{Arithmetic, Checked, Exact, Matrix, Modulo, convert, multiply, power};
+use model::LifeCycle;
+
+mod bigint;
+mod matrix;
+mod model;
 
 fn main() {
     let input = fs::read_to_string("input.txt").unwrap();
     let values = parse_input(&input);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Some(days) = option_value(&args, "--days") {
+        let days: u64 = days.parse().unwrap();
+        let model = parse_model(&args);
+        let fish = model.initial_counts(&values).unwrap();
+
+        if args.iter().any(|arg| arg == "--series") {
+            for (day, count) in population_series(&fish, days as usize, &model, &Exact).unwrap().iter().enumerate() {
+                println!("{},{}", day, count);
+            }
+            return;
+        }
+
+        match option_value(&args, "--mod") {
+            Some(modulus) => match Modulo::new(modulus.parse().unwrap()) {
+                Ok(modulo) => println!("{}", population(&fish, days, &model, &modulo).unwrap()),
+                Err(e) => println!("{}", e),
+            },
+            None if args.iter().any(|arg| arg == "--exact") => println!("{}", population(&fish, days, &model, &Exact).unwrap()),
+            None => match population(&fish, days, &model, &Checked) {
+                Some(count) => println!("{}", count),
+                None => println!("Population overflows, use --exact or --mod <p>"),
+            }
+        }
+        return;
+    }
 
     let part1 = part1(&values);
     let part2 = part2(&values);
@@ -10,6 +43,24 @@ fn main() {
     println!("Part 1: {} Part 2: {}", part1, part2);
 }
 
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
+}
+
+fn parse_model(args: &[String]) -> LifeCycle {
+    let default = LifeCycle::lanternfish();
+    let value = |flag: &str, default: usize| option_value(args, flag).map(|v| v.parse().unwrap()).unwrap_or(default);
+
+    LifeCycle {
+        spawn_interval: value("--interval", default.spawn_interval),
+        maturation_delay: value("--delay", default.maturation_delay),
+        offspring: value("--offspring", default.offspring),
+        death_age: option_value(args, "--death-age").map(|v| v.parse().unwrap()),
+    }
+}
+
 fn parse_input(input: &str) -> Vec<usize> {
     input.split(',').map(|p| p.parse().unwrap()).collect()
 }
@@ -23,21 +74,113 @@ fn part2(values: &[usize]) -> usize {
 }
 
 fn simulate_fish(values: &[usize], days: usize) -> usize {
-    let mut fish = [0_usize; 9];
+    let model = LifeCycle::lanternfish();
+    let fish = model.initial_counts(values).unwrap();
+
+    population(&fish, days as u64, &model, &Checked)
+        .and_then(|count| usize::try_from(count).ok())
+        .expect("Population does not fit in usize")
+}
+
+fn to_column<A: Arithmetic>(fish: &[usize], arithmetic: &A) -> Matrix<A::Value> {
+    fish.iter().map(|count| vec![arithmetic.count(*count)]).collect()
+}
+
+fn sum<A: Arithmetic>(column: &Matrix<A::Value>, arithmetic: &A) -> Option<A::Value> {
+    column.iter().try_fold(arithmetic.count(0), |total, row| arithmetic.add(&total, &row[0]))
+}
+
+fn population<A: Arithmetic>(fish: &[usize], days: u64, model: &LifeCycle, arithmetic: &A) -> Option<A::Value> {
+    let transition = power(&convert(&model.transition_matrix(), arithmetic), days, arithmetic)?;
+
+    sum(&multiply(&transition, &to_column(fish, arithmetic), arithmetic)?, arithmetic)
+}
+
+// Total population for every day from 0 up to and including `days`
+fn population_series<A: Arithmetic>(fish: &[usize], days: usize, model: &LifeCycle, arithmetic: &A) -> Option<Vec<A::Value>> {
+    let transition = convert(&model.transition_matrix(), arithmetic);
+    let mut state = to_column(fish, arithmetic);
+    let mut series = vec![sum(&state, arithmetic)?];
 
-    for age in values {
-        fish[*age] += 1;
+    for _ in 0..days {
+        state = multiply(&transition, &state, arithmetic)?;
+        series.push(sum(&state, arithmetic)?);
     }
 
-    (0..days).fold(fish, |fish, _| simulate_day(&fish))
-        .iter().sum()
+    Some(series)
 }
 
-fn simulate_day(input: &[usize; 9]) -> [usize; 9] {
-    let mut new_fish = [0_usize; 9];
-    
-    new_fish[..(input.len() - 1)].clone_from_slice(&input[1..]);
-    new_fish[8] = input[0];
-    new_fish[6] += input[0];
-    new_fish
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_should_work() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part1(&values);
+
+        assert_eq!(5934, result);
+    }
+
+    #[test]
+    fn part2_should_work() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part2(&values);
+
+        assert_eq!(26984457539, result);
+    }
+
+    #[test]
+    fn population_should_match_daily_simulation() {
+        let model = LifeCycle::lanternfish();
+        let fish = model.initial_counts(&parse_input("3,4,3,1,2")).unwrap();
+        let mut day = fish.clone();
+
+        for days in 0..300 {
+            assert_eq!(day.iter().sum::<usize>() as u128, population(&fish, days, &model, &Checked).unwrap());
+            assert_eq!(day.iter().sum::<usize>().to_string(), population(&fish, days, &model, &Exact).unwrap().to_string());
+            day = model.simulate_day(&day);
+        }
+    }
+
+    #[test]
+    fn population_should_support_huge_day_counts() {
+        let model = LifeCycle::lanternfish();
+        let fish = model.initial_counts(&parse_input("3,4,3,1,2")).unwrap();
+        let modulus = 1_000_000_007;
+
+        assert!(population(&fish, 10_000, &model, &Checked).is_none());
+
+        let exact = population(&fish, 10_000, &model, &Exact).unwrap();
+        let (_, remainder) = exact.divide_small(modulus as u32);
+        assert_eq!(remainder as u64, population(&fish, 10_000, &model, &Modulo::new(modulus).unwrap()).unwrap());
+
+        assert!(population(&fish, 1_000_000_000_000, &model, &Modulo::new(modulus).unwrap()).unwrap() < modulus);
+        assert!(Modulo::new(0).is_err());
+        assert_eq!(Some(0), population(&fish, 5, &model, &Modulo::new(1).unwrap()));
+    }
+
+    #[test]
+    fn late_death_age_should_not_change_early_population() {
+        let timers = parse_input("3,4,3,1,2");
+        let immortal = LifeCycle::lanternfish();
+        let mortal = LifeCycle { death_age: Some(100), ..LifeCycle::lanternfish() };
+
+        let expected = population_series(&immortal.initial_counts(&timers).unwrap(), 90, &immortal, &Checked).unwrap();
+        let result = population_series(&mortal.initial_counts(&timers).unwrap(), 90, &mortal, &Checked).unwrap();
+
+        assert_eq!(expected, result);
+        assert_eq!(vec![5, 5, 6, 7, 9, 10], result[..6].to_vec());
+    }
+
+    #[test]
+    fn custom_life_cycle_should_work() {
+        let model = LifeCycle { spawn_interval: 2, maturation_delay: 0, offspring: 2, death_age: Some(3) };
+        let fish = model.initial_counts(&[1]).unwrap();
+        let series = population_series(&fish, 5, &model, &Checked).unwrap();
+
+        assert_eq!(vec![1, 1, 3, 2, 6, 4], series);
+    }
 }
\ No newline at end of file
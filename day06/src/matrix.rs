@@ -0,0 +1,126 @@
+use crate::bigint::BigUint;
+
+pub trait Arithmetic {
+    type Value: Clone;
+
+    fn count(&self, count: usize) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value>;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Option<Self::Value>;
+}
+
+// Plain integers, failing instead of overflowing
+pub struct Checked;
+
+impl Arithmetic for Checked {
+    type Value = u128;
+
+    fn count(&self, count: usize) -> u128 {
+        count as u128
+    }
+
+    fn add(&self, a: &u128, b: &u128) -> Option<u128> {
+        a.checked_add(*b)
+    }
+
+    fn mul(&self, a: &u128, b: &u128) -> Option<u128> {
+        a.checked_mul(*b)
+    }
+}
+
+pub struct Modulo(u64);
+
+impl Modulo {
+    pub fn new(modulus: u64) -> Result<Modulo, String> {
+        if modulus == 0 {
+            return Err(String::from("The modulus has to be at least 1"));
+        }
+
+        Ok(Modulo(modulus))
+    }
+}
+
+impl Arithmetic for Modulo {
+    type Value = u64;
+
+    fn count(&self, count: usize) -> u64 {
+        (count as u64) % self.0
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> Option<u64> {
+        Some(((*a as u128 + *b as u128) % self.0 as u128) as u64)
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> Option<u64> {
+        Some(((*a as u128 * *b as u128) % self.0 as u128) as u64)
+    }
+}
+
+pub struct Exact;
+
+impl Arithmetic for Exact {
+    type Value = BigUint;
+
+    fn count(&self, count: usize) -> BigUint {
+        BigUint::from_u64(count as u64)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> Option<BigUint> {
+        Some(a.add(b))
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> Option<BigUint> {
+        Some(a.mul(b))
+    }
+}
+
+pub type Matrix<T> = Vec<Vec<T>>;
+
+pub fn convert<A: Arithmetic>(matrix: &Matrix<usize>, arithmetic: &A) -> Matrix<A::Value> {
+    matrix.iter()
+        .map(|row| row.iter().map(|v| arithmetic.count(*v)).collect())
+        .collect()
+}
+
+fn identity<A: Arithmetic>(size: usize, arithmetic: &A) -> Matrix<A::Value> {
+    (0..size)
+        .map(|y| (0..size).map(|x| arithmetic.count((x == y) as usize)).collect())
+        .collect()
+}
+
+pub fn multiply<A: Arithmetic>(a: &Matrix<A::Value>, b: &Matrix<A::Value>, arithmetic: &A) -> Option<Matrix<A::Value>> {
+    let mut result = vec![];
+
+    for row in a.iter() {
+        let mut result_row = vec![arithmetic.count(0); b[0].len()];
+
+        for (value, b_row) in row.iter().zip(b.iter()) {
+            for (sum, b_value) in result_row.iter_mut().zip(b_row.iter()) {
+                *sum = arithmetic.add(sum, &arithmetic.mul(value, b_value)?)?;
+            }
+        }
+
+        result.push(result_row);
+    }
+
+    Some(result)
+}
+
+// Exponentiation by squaring, O(log exponent) matrix multiplications
+pub fn power<A: Arithmetic>(matrix: &Matrix<A::Value>, exponent: u64, arithmetic: &A) -> Option<Matrix<A::Value>> {
+    let mut result = identity(matrix.len(), arithmetic);
+    let mut base = matrix.clone();
+    let mut exponent = exponent;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply(&result, &base, arithmetic)?;
+        }
+
+        exponent >>= 1;
+        if exponent > 0 {
+            base = multiply(&base, &base, arithmetic)?;
+        }
+    }
+
+    Some(result)
+}
@@ -0,0 +1,106 @@
+use crate::matrix::Matrix;
+
+// Without a death age every state is a timer, counting down to the next spawn.
+// With a death age the state is the age of the fish instead, since fish with the
+// same timer can have different amounts of life left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifeCycle {
+    pub spawn_interval: usize,
+    pub maturation_delay: usize,
+    pub offspring: usize,
+    pub death_age: Option<usize>,
+}
+
+impl LifeCycle {
+    pub fn lanternfish() -> LifeCycle {
+        LifeCycle {
+            spawn_interval: 7,
+            maturation_delay: 2,
+            offspring: 1,
+            death_age: None,
+        }
+    }
+
+    fn newborn_timer(&self) -> usize {
+        self.spawn_interval - 1 + self.maturation_delay
+    }
+
+    pub fn states(&self) -> usize {
+        self.death_age.unwrap_or(self.newborn_timer() + 1)
+    }
+
+    // Initial fish with timer t are treated as newborns that have aged until their timer reached t
+    pub fn initial_counts(&self, timers: &[usize]) -> Result<Vec<usize>, String> {
+        if self.spawn_interval == 0 {
+            return Err(String::from("Spawn interval must be at least 1"));
+        }
+
+        let mut counts = vec![0; self.states()];
+
+        for timer in timers {
+            if *timer > self.newborn_timer() {
+                return Err(format!("Timer {} is above the newborn timer {}", timer, self.newborn_timer()));
+            }
+
+            let state = match self.death_age {
+                Some(_) => self.newborn_timer() - timer,
+                None => *timer,
+            };
+
+            match counts.get_mut(state) {
+                Some(count) => *count += 1,
+                None => return Err(format!("Fish with timer {} would already have died", timer)),
+            }
+        }
+
+        Ok(counts)
+    }
+
+    fn successors(&self, state: usize) -> Vec<(usize, usize)> {
+        match self.death_age {
+            None if state == 0 => vec![(self.spawn_interval - 1, 1), (self.newborn_timer(), self.offspring)],
+            None => vec![(state - 1, 1)],
+            Some(death_age) => {
+                let age = state + 1;
+                let first_spawn = self.newborn_timer() + 1;
+                let mut successors = vec![];
+
+                if age < death_age {
+                    successors.push((age, 1));
+                }
+
+                // A fish that spawns on the day it reaches its death age still has its offspring
+                if age >= first_spawn && (age - first_spawn).is_multiple_of(self.spawn_interval) {
+                    successors.push((0, self.offspring));
+                }
+
+                successors
+            }
+        }
+    }
+
+    pub fn simulate_day(&self, fish: &[usize]) -> Vec<usize> {
+        let mut new_fish = vec![0; self.states()];
+
+        for (state, count) in fish.iter().enumerate() {
+            for (next, multiplier) in self.successors(state) {
+                new_fish[next] += count * multiplier;
+            }
+        }
+
+        new_fish
+    }
+
+    // Column j holds the fish that a single fish in state j turns into after one day
+    pub fn transition_matrix(&self) -> Matrix<usize> {
+        let columns: Vec<Vec<usize>> = (0..self.states()).map(|j| {
+            let mut fish = vec![0; self.states()];
+            fish[j] = 1;
+            self.simulate_day(&fish)
+        }).collect();
+
+        (0..self.states())
+            .map(|i| columns.iter().map(|column| column[i]).collect())
+            .collect()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,248 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use itertools::Itertools;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crab {
+    pub position: i64,
+    pub weight: i64,
+}
+
+impl Crab {
+    pub fn new(position: i64) -> Crab {
+        Crab { position, weight: 1 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alignment {
+    pub position: i64,
+    pub fuel: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiAlignment {
+    pub positions: Vec<i64>,
+    pub fuel: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignError {
+    NoCrabs,
+    NoTargets,
+    Overflow,
+}
+
+impl fmt::Display for AlignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlignError::NoCrabs => write!(f, "There are no crabs to align"),
+            AlignError::NoTargets => write!(f, "At least one target position is needed"),
+            AlignError::Overflow => write!(f, "The fuel cost doesn't fit in 64 bits"),
+        }
+    }
+}
+
+// Costs return None when they overflow
+pub trait CostFunction {
+    fn cost(&self, distance: i64) -> Option<i64>;
+
+    // The positions that contain the optimum, for cost functions that have a closed form
+    fn candidates(&self, _crabs: &[Crab]) -> Option<RangeInclusive<i64>> {
+        None
+    }
+}
+
+pub struct Linear;
+
+impl CostFunction for Linear {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        Some(distance)
+    }
+
+    // The first position where at least half of the total weight lies at or before it
+    fn candidates(&self, crabs: &[Crab]) -> Option<RangeInclusive<i64>> {
+        let total: i64 = crabs.iter().map(|c| c.weight).sum();
+        let mut cumulative = 0;
+
+        let median = crabs.iter()
+            .sorted_by_key(|c| c.position)
+            .find(|c| {
+                cumulative += c.weight;
+                cumulative * 2 >= total
+            })?;
+
+        Some(median.position..=median.position)
+    }
+}
+
+pub struct Triangular;
+
+impl CostFunction for Triangular {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        distance.checked_mul(distance + 1).map(|product| product / 2)
+    }
+
+    // The optimum lies within half a step of the weighted mean
+    fn candidates(&self, crabs: &[Crab]) -> Option<RangeInclusive<i64>> {
+        let (floor, ceil) = mean(crabs)?;
+        Some((floor - 1)..=(ceil + 1))
+    }
+}
+
+pub struct Quadratic;
+
+impl CostFunction for Quadratic {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        distance.checked_mul(distance)
+    }
+
+    fn candidates(&self, crabs: &[Crab]) -> Option<RangeInclusive<i64>> {
+        let (floor, ceil) = mean(crabs)?;
+        Some(floor..=ceil)
+    }
+}
+
+// Any cost function; solved by ternary search, so it has to be convex
+pub struct Custom<F: Fn(i64) -> Option<i64>>(pub F);
+
+impl<F: Fn(i64) -> Option<i64>> CostFunction for Custom<F> {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        (self.0)(distance)
+    }
+}
+
+fn mean(crabs: &[Crab]) -> Option<(i64, i64)> {
+    let sum: i64 = crabs.iter().map(|c| c.position * c.weight).sum();
+    let count: i64 = crabs.iter().map(|c| c.weight).sum();
+
+    if count == 0 {
+        return None;
+    }
+
+    let floor = sum.div_euclid(count);
+    Some((floor, if sum.rem_euclid(count) == 0 { floor } else { floor + 1 }))
+}
+
+// None when the total doesn't fit in an i64
+pub fn total_fuel(crabs: &[Crab], target: i64, cost: &dyn CostFunction) -> Option<i64> {
+    crabs.iter().try_fold(0i64, |total, c| {
+        let fuel = cost.cost((c.position - target).abs())?.checked_mul(c.weight)?;
+        total.checked_add(fuel)
+    })
+}
+
+// Orders fuel totals with an overflow above any total that fits
+fn fuel_key(fuel: Option<i64>) -> (bool, Option<i64>) {
+    (fuel.is_none(), fuel)
+}
+
+// Lowest fuel, with ties going to the smallest position. Positions that overflow are skipped,
+// so this only fails when every one of them does.
+fn best_of(crabs: &[Crab], candidates: impl Iterator<Item = i64>, cost: &dyn CostFunction) -> Result<Alignment, AlignError> {
+    let mut candidates = candidates.peekable();
+    if candidates.peek().is_none() {
+        return Err(AlignError::NoCrabs);
+    }
+
+    candidates
+        .filter_map(|position| total_fuel(crabs, position, cost).map(|fuel| Alignment { position, fuel }))
+        .min_by_key(|alignment| (alignment.fuel, alignment.position))
+        .ok_or(AlignError::Overflow)
+}
+
+#[cfg(test)]
+pub fn brute_force(crabs: &[Crab], cost: &dyn CostFunction) -> Result<Alignment, AlignError> {
+    let min = crabs.iter().map(|c| c.position).min().ok_or(AlignError::NoCrabs)?;
+    let max = crabs.iter().map(|c| c.position).max().ok_or(AlignError::NoCrabs)?;
+
+    best_of(crabs, min..=max, cost)
+}
+
+pub fn ternary_search(crabs: &[Crab], cost: &dyn CostFunction) -> Result<Alignment, AlignError> {
+    let mut low = crabs.iter().map(|c| c.position).min().ok_or(AlignError::NoCrabs)?;
+    let mut high = crabs.iter().map(|c| c.position).max().ok_or(AlignError::NoCrabs)?;
+
+    while high - low > 2 {
+        let m1 = low + (high - low) / 3;
+        let m2 = high - (high - low) / 3;
+
+        if fuel_key(total_fuel(crabs, m1, cost)) <= fuel_key(total_fuel(crabs, m2, cost)) {
+            high = m2;
+        } else {
+            low = m1 + 1;
+        }
+    }
+
+    best_of(crabs, low..=high, cost)
+}
+
+pub fn align(crabs: &[Crab], cost: &dyn CostFunction) -> Result<Alignment, AlignError> {
+    match cost.candidates(crabs) {
+        Some(candidates) => best_of(crabs, candidates, cost),
+        None => ternary_search(crabs, cost),
+    }
+}
+
+// 1-D k-median: with every crab moving to its nearest target, the crabs sharing a
+// target form a contiguous run in position order. dp[c][j] is the cheapest way to
+// serve the first j positions with c targets, built from the best single alignment
+// of every run of positions.
+// Runs whose fuel overflows are left out, so this only fails if every split overflows.
+pub fn align_multiple(crabs: &[Crab], targets: usize, cost: &dyn CostFunction) -> Result<MultiAlignment, AlignError> {
+    let groups = crabs.iter()
+        .sorted_by_key(|c| c.position)
+        .group_by(|c| c.position)
+        .into_iter()
+        .map(|(position, group)| Crab { position, weight: group.map(|c| c.weight).sum() })
+        .collect_vec();
+
+    if targets == 0 {
+        return Err(AlignError::NoTargets);
+    }
+
+    let n = groups.len();
+    let targets = targets.min(n);
+    if targets == 0 {
+        return Err(AlignError::NoCrabs);
+    }
+
+    let runs: Vec<Vec<Option<Alignment>>> = (0..n)
+        .map(|start| (start..n).map(|end| align(&groups[start..=end], cost).ok()).collect())
+        .collect();
+
+    let mut fuel = vec![vec![i64::MAX; n + 1]; targets + 1];
+    let mut split = vec![vec![0; n + 1]; targets + 1];
+    fuel[0][0] = 0;
+
+    for c in 1..=targets {
+        for j in c..=n {
+            for i in (c - 1)..j {
+                if fuel[c - 1][i] == i64::MAX {
+                    continue;
+                }
+
+                let candidate = runs[i][j - 1 - i].and_then(|run| fuel[c - 1][i].checked_add(run.fuel));
+                if let Some(candidate) = candidate.filter(|candidate| *candidate < fuel[c][j]) {
+                    fuel[c][j] = candidate;
+                    split[c][j] = i;
+                }
+            }
+        }
+    }
+
+    if fuel[targets][n] == i64::MAX {
+        return Err(AlignError::Overflow);
+    }
+
+    let mut positions = vec![];
+    let mut end = n;
+    for c in (1..=targets).rev() {
+        let start = split[c][end];
+        positions.push(runs[start][end - 1 - start].unwrap().position);
+        end = start;
+    }
+
+    positions.reverse();
+    Ok(MultiAlignment { positions, fuel: fuel[targets][n] })
+}
@@ -1,8 +1,37 @@
-use std::fs;
+use std::{env, fs};
+use cost::{Crab, CostFunction, Custom, Linear, Quadratic, Triangular, align, align_multiple};
+
+mod cost;
 
 fn main() {
     let input = fs::read_to_string("input.txt").unwrap();
-    let values = parse_input(&input);
+    let values = match parse_input(&input) {
+        Ok(values) => values,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let cost = option_value(&args, "--cost").map(|name| parse_cost(name).unwrap());
+
+    if let Some(targets) = option_value(&args, "--targets") {
+        let cost = cost.unwrap_or_else(|| Box::new(Linear));
+        match align_multiple(&values, targets.parse().unwrap(), cost.as_ref()) {
+            Ok(alignment) => println!("Positions: {:?} Fuel: {}", alignment.positions, alignment.fuel),
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
+
+    if let Some(cost) = cost {
+        match align(&values, cost.as_ref()) {
+            Ok(alignment) => println!("Position: {} Fuel: {}", alignment.position, alignment.fuel),
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
 
     let part1 = part1(&values);
     let part2 = part2(&values);
@@ -10,43 +39,64 @@ fn main() {
     println!("Part 1: {} Part 2: {}", part1, part2);
 }
 
-fn parse_input(input: &str) -> Vec<i32> {
-    input.split(',').map(|v| v.parse().unwrap()).collect()
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
 }
 
-fn part1(values: &[i32]) -> i32 {
-    let min = *values.iter().min().unwrap();
-    let max = *values.iter().max().unwrap();
-    
-    (min..max)
-        .map(|i| values.iter().map(|v| 
-            (v - i).abs()).sum())
-        .min().unwrap()
+// Crabs are given as positions, optionally followed by a fuel weight as in "16:3". Weights
+// can't be negative, as the solvers rely on the total fuel being convex.
+fn parse_input(input: &str) -> Result<Vec<Crab>, String> {
+    input.trim().split(',').map(|v| match v.split_once(':') {
+        Some((position, weight)) => {
+            let position = parse_value(position, "position")?;
+            let weight = parse_value(weight, "weight")?;
+
+            if weight < 0 {
+                return Err(format!("Weight {} of the crab at {} is negative", weight, position));
+            }
+
+            Ok(Crab { position, weight })
+        },
+        None => Ok(Crab::new(parse_value(v, "position")?)),
+    }).collect()
 }
 
-fn part2(values: &[i32]) -> i32 {
-    let min = *values.iter().min().unwrap();
-    let max = *values.iter().max().unwrap();
+fn parse_value(value: &str, name: &str) -> Result<i64, String> {
+    value.parse().map_err(|_| format!("Invalid {} '{}'", name, value))
+}
 
-    (min..max)
-        .map(|i| values.iter().map(|v|
-                triangular_number((v - i).abs()))
-            .sum())
-        .min().unwrap()
+fn parse_cost(name: &str) -> Result<Box<dyn CostFunction>, String> {
+    match name {
+        "linear" => Ok(Box::new(Linear)),
+        "triangular" => Ok(Box::new(Triangular)),
+        "quadratic" => Ok(Box::new(Quadratic)),
+        _ => match name.strip_prefix("power:").map(|exponent| exponent.parse::<u32>()) {
+            Some(Ok(exponent)) if exponent >= 1 => Ok(Box::new(Custom(move |distance: i64| distance.checked_pow(exponent)))),
+            _ => Err(format!("Unknown cost function {}", name)),
+        }
+    }
 }
 
-fn triangular_number(size: i32) -> i32 {
-    (size * (size+1)) / 2
+fn part1(values: &[Crab]) -> i64 {
+    align(values, &Linear).unwrap().fuel
+}
+
+fn part2(values: &[Crab]) -> i64 {
+    align(values, &Triangular).unwrap().fuel
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cost::{AlignError, Alignment};
+    use itertools::Itertools;
 
     #[test]
     fn part1_should_work() {
         let input = fs::read_to_string("input.txt").unwrap();
-        let values = parse_input(&input);
+        let values = parse_input(&input).unwrap();
     
         let part1 = part1(&values);
 
@@ -56,10 +106,88 @@ mod tests {
     #[test]
     fn part2_should_work() {
         let input = fs::read_to_string("input.txt").unwrap();
-        let values = parse_input(&input);
+        let values = parse_input(&input).unwrap();
     
         let part1 = part2(&values);
 
         assert_eq!(105461913, part1);
     }
+
+    fn assert_matches_brute_force(values: &[Crab], cost: &dyn CostFunction) {
+        let expected: Result<Alignment, AlignError> = cost::brute_force(values, cost);
+
+        assert_eq!(expected, align(values, cost));
+        assert_eq!(expected, cost::ternary_search(values, cost));
+    }
+
+    #[test]
+    fn align_should_match_brute_force() {
+        let example = parse_input("16,1,2,0,4,2,7,1,2,14").unwrap();
+        let skewed = parse_input("0,0,0,0,1,1000,-40,3,3").unwrap();
+        let even = parse_input("1,2,5,9").unwrap();
+        let weighted = parse_input("1:5,2,5:3,9:12,-3:2,40:0").unwrap();
+
+        for values in [example, skewed, even, weighted] {
+            assert_matches_brute_force(&values, &Linear);
+            assert_matches_brute_force(&values, &Triangular);
+            assert_matches_brute_force(&values, &Quadratic);
+            assert_matches_brute_force(&values, parse_cost("power:3").unwrap().as_ref());
+        }
+    }
+
+    #[test]
+    fn align_should_include_the_rightmost_position() {
+        let values = parse_input("0,10,10,10").unwrap();
+
+        assert_eq!(Ok(Alignment { position: 10, fuel: 10 }), align(&values, &Linear));
+    }
+
+    #[test]
+    fn align_multiple_should_match_exhaustive_search() {
+        let values = parse_input("16,1,2:4,0,4,2,7:2,1,2,14:3,30,31").unwrap();
+
+        for targets in 1..=3 {
+            for cost in [parse_cost("linear").unwrap(), parse_cost("triangular").unwrap()] {
+                let expected = (0..=31).combinations(targets)
+                    .map(|positions| values.iter()
+                        .map(|crab| positions.iter().map(|p| crab.weight * cost.cost((crab.position - p).abs()).unwrap()).min().unwrap())
+                        .sum::<i64>())
+                    .min()
+                    .unwrap();
+
+                assert_eq!(Ok(expected), align_multiple(&values, targets, cost.as_ref()).map(|a| a.fuel));
+            }
+        }
+    }
+
+    #[test]
+    fn align_multiple_should_use_one_target_per_position_at_most() {
+        let values = parse_input("3,3,8").unwrap();
+        let alignment = align_multiple(&values, 5, &Linear).unwrap();
+
+        assert_eq!(vec![3, 8], alignment.positions);
+        assert_eq!(0, alignment.fuel);
+        assert_eq!(Err(AlignError::NoTargets), align_multiple(&values, 0, &Linear).map(|a| a.fuel));
+    }
+
+    #[test]
+    fn parse_input_should_reject_negative_weights() {
+        assert_eq!(Err(String::from("Weight -3 of the crab at 4 is negative")), parse_input("1,4:-3"));
+        assert!(parse_input("1,x").is_err());
+        assert_eq!(Ok(vec![Crab { position: 1, weight: 1 }, Crab { position: 4, weight: 0 }]), parse_input("1,4:0"));
+    }
+
+    #[test]
+    fn align_should_report_overflow() {
+        let input = fs::read_to_string("input.txt").unwrap();
+        let values = parse_input(&input).unwrap();
+        let cost = parse_cost("power:6").unwrap();
+
+        assert_eq!(Err(AlignError::Overflow), align(&values, cost.as_ref()));
+        assert_eq!(Err(AlignError::Overflow), align_multiple(&parse_input("0,10000000").unwrap(), 1, cost.as_ref()).map(|a| a.fuel));
+        assert_eq!(Ok(0), align_multiple(&parse_input("0,10000000").unwrap(), 2, cost.as_ref()).map(|a| a.fuel));
+        assert_eq!(Ok(Alignment { position: 0, fuel: 1 << 41 }), align(&parse_input("-1024,1024").unwrap(), parse_cost("power:4").unwrap().as_ref()));
+        assert_eq!(Err(AlignError::Overflow), align(&parse_input("0,1000000").unwrap(), parse_cost("power:4").unwrap().as_ref()));
+        assert_eq!(Err(AlignError::NoCrabs), align(&[], &Linear));
+    }
 }
\ No newline at end of file
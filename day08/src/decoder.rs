@@ -0,0 +1,132 @@
+use std::fmt;
+
+// Sets of wires or segments, bit i standing for wire/segment i
+pub type Pattern = u32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    // No wiring turns every signal into a known glyph
+    Inconsistent,
+    // More than one wiring fits the signals
+    Ambiguous,
+    UnknownWire(char),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Inconsistent => write!(f, "no wiring matches the signals"),
+            DecodeError::Ambiguous => write!(f, "signals match more than one wiring"),
+            DecodeError::UnknownWire(wire) => write!(f, "unknown wire {}", wire),
+        }
+    }
+}
+
+pub fn to_pattern(signal: &str, wires: usize) -> Result<Pattern, DecodeError> {
+    signal.chars().try_fold(0, |pattern, c| {
+        let wire = (c as u32).wrapping_sub('a' as u32);
+
+        if (wire as usize) < wires {
+            Ok(pattern | (1 << wire))
+        } else {
+            Err(DecodeError::UnknownWire(c))
+        }
+    })
+}
+
+// Wire i is connected to segment mapping[i]
+pub fn apply(pattern: Pattern, mapping: &[usize]) -> Pattern {
+    mapping.iter().enumerate()
+        .filter(|(wire, _)| pattern & (1 << wire) != 0)
+        .fold(0, |segments, (_, segment)| segments | (1 << segment))
+}
+
+struct Solver<'a> {
+    signals: &'a [Pattern],
+    glyphs: &'a [Pattern],
+    segments: usize,
+    solutions: Vec<Vec<usize>>,
+}
+
+impl<'a> Solver<'a> {
+    fn candidates(&self, signal: Pattern) -> impl Iterator<Item = Pattern> + '_ {
+        self.glyphs.iter().copied().filter(move |glyph| glyph.count_ones() == signal.count_ones())
+    }
+
+    // Narrows down the segments each wire can drive: a wire in a signal drives a segment
+    // of one of the glyphs of the same size, a wire outside it one outside such a glyph
+    fn propagate(&self, possible: &mut [Pattern]) {
+        let all: Pattern = (1 << self.segments) - 1;
+
+        for signal in self.signals.iter() {
+            let inside = self.candidates(*signal).fold(0, |acc, glyph| acc | glyph);
+            let outside = self.candidates(*signal).fold(0, |acc, glyph| acc | (all & !glyph));
+
+            for (wire, segments) in possible.iter_mut().enumerate() {
+                *segments &= if signal & (1 << wire) != 0 { inside } else { outside };
+            }
+        }
+    }
+
+    // A partial wiring is valid while every signal can still become a glyph of the same size
+    fn consistent(&self, mapping: &[Option<usize>]) -> bool {
+        self.signals.iter().all(|signal| {
+            let mut inside = 0;
+            let mut outside = 0;
+
+            for (wire, segment) in mapping.iter().enumerate() {
+                if let Some(segment) = segment {
+                    if signal & (1 << wire) != 0 {
+                        inside |= 1 << segment;
+                    } else {
+                        outside |= 1 << segment;
+                    }
+                }
+            }
+
+            self.candidates(*signal).any(|glyph| glyph & inside == inside && glyph & outside == 0)
+        })
+    }
+
+    fn search(&mut self, possible: &[Pattern], mapping: &mut Vec<Option<usize>>, used: Pattern) {
+        if self.solutions.len() > 1 || !self.consistent(mapping) {
+            return;
+        }
+
+        let next = (0..self.segments)
+            .filter(|wire| mapping[*wire].is_none())
+            .min_by_key(|wire| (possible[*wire] & !used).count_ones());
+
+        let wire = match next {
+            Some(wire) => wire,
+            None => {
+                self.solutions.push(mapping.iter().map(|segment| segment.unwrap()).collect());
+                return;
+            }
+        };
+
+        for segment in 0..self.segments {
+            if (possible[wire] & !used) & (1 << segment) != 0 {
+                mapping[wire] = Some(segment);
+                self.search(possible, mapping, used | (1 << segment));
+                mapping[wire] = None;
+            }
+        }
+    }
+}
+
+// Finds the one wiring that turns every signal into one of the glyphs. There are as many
+// wires as there are segments in the display.
+pub fn deduce_mapping(signals: &[Pattern], glyphs: &[Pattern], segments: usize) -> Result<Vec<usize>, DecodeError> {
+    let mut solver = Solver { signals, glyphs, segments, solutions: vec![] };
+    let mut possible = vec![(1 << segments) - 1; segments];
+
+    solver.propagate(&mut possible);
+    solver.search(&possible, &mut vec![None; segments], 0);
+
+    match solver.solutions.len() {
+        0 => Err(DecodeError::Inconsistent),
+        1 => Ok(solver.solutions.remove(0)),
+        _ => Err(DecodeError::Ambiguous),
+    }
+}
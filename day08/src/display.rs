@@ -0,0 +1,138 @@
+use crate::decoder::{DecodeError, Pattern, to_pattern};
+
+// A display definition: how many segments it has, and the segments lit for every glyph.
+// Definitions are written one glyph per line as "<glyph> <segments>", with segments named
+// from 'a' onwards, an optional "segments <count>" line and '#' comments. Lines starting
+// with '|' draw the layout of a single glyph, using the segment names as pixels.
+#[derive(Debug, Clone)]
+pub struct Display {
+    pub segments: usize,
+    pub glyphs: Vec<(Pattern, char)>,
+    pub layout: Vec<String>,
+}
+
+impl Display {
+    pub fn seven_segment() -> Display {
+        Display::parse(include_str!("../displays/seven_segment.txt")).unwrap()
+    }
+
+    pub fn parse(input: &str) -> Result<Display, String> {
+        let layout: Vec<String> = input.lines()
+            .filter_map(|line| line.trim_start().strip_prefix('|'))
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect();
+        let width = layout.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let layout = layout.iter().map(|line| format!("{:<width$}", line, width = width)).collect();
+
+        let lines: Vec<&str> = input.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('|'))
+            .collect();
+
+        let declared = lines.iter()
+            .find_map(|line| line.strip_prefix("segments "))
+            .map(|count| count.trim().parse::<usize>().map_err(|_| format!("Invalid segment count {}", count)))
+            .transpose()?;
+
+        let definitions = lines.iter()
+            .filter(|line| !line.starts_with("segments "))
+            .map(|line| {
+                let (glyph, segments) = line.split_once(' ').ok_or_else(|| format!("Invalid glyph definition {}", line))?;
+                let mut chars = glyph.chars();
+
+                match (chars.next(), chars.next()) {
+                    (Some(glyph), None) => Ok((glyph, segments.trim())),
+                    _ => Err(format!("Glyph {} should be a single character", glyph)),
+                }
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let used = definitions.iter()
+            .flat_map(|(_, segments)| segments.chars())
+            .map(|c| (c as usize).saturating_sub('a' as usize) + 1)
+            .max()
+            .unwrap_or(0);
+        let segments = declared.unwrap_or(used);
+
+        if segments > 26 {
+            return Err(format!("Displays can have at most 26 segments, not {}", segments));
+        }
+
+        let mut glyphs: Vec<(Pattern, char)> = vec![];
+        for (glyph, lit) in definitions {
+            let pattern = to_pattern(lit, segments).map_err(|e| format!("Glyph {}: {}", glyph, e))?;
+
+            if let Some((_, other)) = glyphs.iter().find(|(existing, _)| *existing == pattern) {
+                return Err(format!("Glyphs {} and {} light the same segments", other, glyph));
+            }
+
+            glyphs.push((pattern, glyph));
+        }
+
+        Ok(Display { segments, glyphs, layout })
+    }
+
+    pub fn patterns(&self) -> Vec<Pattern> {
+        self.glyphs.iter().map(|(pattern, _)| *pattern).collect()
+    }
+
+    pub fn decode(&self, segments: Pattern) -> Result<char, DecodeError> {
+        self.glyphs.iter()
+            .find(|(pattern, _)| *pattern == segments)
+            .map(|(_, glyph)| *glyph)
+            .ok_or(DecodeError::Inconsistent)
+    }
+
+    fn glyph_width(&self) -> usize {
+        self.layout.first().map(|line| line.chars().count()).unwrap_or(0)
+    }
+
+    // Draws the glyphs side by side, lit segments by name and unlit ones as '.'
+    pub fn render(&self, patterns: &[Pattern]) -> String {
+        self.layout.iter().map(|line| {
+            patterns.iter().map(|pattern| {
+                line.chars().map(|c| match segment_index(c) {
+                    Some(segment) if pattern & (1 << segment) != 0 => c,
+                    Some(_) => '.',
+                    None => c,
+                }).collect::<String>()
+            }).collect::<Vec<String>>().join("  ").trim_end().to_string()
+        }).map(|line| line + "\n").collect()
+    }
+
+    // Reads back art as produced by render, any segment name drawn in a glyph lights it
+    pub fn parse_art(&self, art: &str) -> Result<Vec<Pattern>, DecodeError> {
+        let stride = self.glyph_width() + 2;
+        let mut patterns: Vec<Pattern> = vec![];
+
+        for line in art.lines() {
+            for (column, c) in line.chars().enumerate() {
+                if let Some(segment) = segment_index(c) {
+                    if segment >= self.segments {
+                        return Err(DecodeError::UnknownWire(c));
+                    }
+
+                    let glyph = column / stride;
+                    if patterns.len() <= glyph {
+                        patterns.resize(glyph + 1, 0);
+                    }
+                    patterns[glyph] |= 1 << segment;
+                }
+            }
+        }
+
+        Ok(patterns)
+    }
+
+    pub fn read_art(&self, art: &str) -> Result<String, DecodeError> {
+        self.parse_art(art)?.iter().map(|pattern| self.decode(*pattern)).collect()
+    }
+}
+
+fn segment_index(c: char) -> Option<usize> {
+    if c.is_ascii_lowercase() {
+        Some(c as usize - 'a' as usize)
+    } else {
+        None
+    }
+}
\ No newline at end of file
@@ -1,6 +1,12 @@
-use std::{fs, collections::HashMap};
+use std::fs;
+use std::env;
+use decoder::{DecodeError, Pattern, apply, deduce_mapping, to_pattern};
+use display::Display;
 use itertools::Itertools;
 
+mod decoder;
+mod display;
+
 struct Entry {
     signals: Vec<String>,
     output: Vec<String>
@@ -9,11 +15,38 @@ struct Entry {
 fn main() {
     let input = fs::read_to_string("input.txt").unwrap();
     let values = parse_input(&input);
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let part1 = part1(&values);
-    let part2 = part2(&values);
+    let display = args.iter().position(|arg| arg == "--display")
+        .map(|index| Display::parse(&fs::read_to_string(&args[index + 1]).unwrap()).unwrap());
+
+    if args.iter().any(|arg| arg == "--show") {
+        let display = display.unwrap_or_else(Display::seven_segment);
+
+        for (index, entry) in values.iter().enumerate() {
+            match show_entry(entry, &display) {
+                Ok(text) => println!("Entry {}\n{}", index + 1, text),
+                Err(error) => println!("Entry {}: {}\n", index + 1, error),
+            }
+        }
+        return;
+    }
+
+    if let Some(display) = display {
+        for (index, entry) in values.iter().enumerate() {
+            match decode_entry(entry, &display) {
+                Ok(text) => println!("{}", text),
+                Err(error) => println!("Entry {}: {}", index + 1, error),
+            }
+        }
+        return;
+    }
 
-    println!("Part 1: {} Part 2: {}", part1, part2);
+    let part1 = part1(&values);
+    match part2(&values) {
+        Ok(part2) => println!("Part 1: {} Part 2: {}", part1, part2),
+        Err(error) => println!("Part 1: {} Part 2 failed: {}", part1, error),
+    }
 }
 
 fn parse_input(input: &str) -> Vec<Entry> {
@@ -31,61 +64,57 @@ fn parse_line(line: &str) -> Entry {
 
 fn part1(entries: &[Entry]) -> usize {
     entries.iter().map(|entry| {
-        entry.output.iter().filter(|output| vec![2, 4, 3, 7].contains(&(output.len() as i32))).count()
+        entry.output.iter().filter(|output| [2, 4, 3, 7].contains(&(output.len() as i32))).count()
     }).sum()
 }
 
-fn part2(values: &[Entry]) -> i32 {
-    let permutations = generate_permutations();
-    let mut sum = 0;
-    for entry in values {
-        let signal_mapping = brute_force_signal_mapping(&entry.signals, &permutations);
-        let digits: String = entry.output.iter().map(|signal| decode_signal(signal, &signal_mapping).unwrap()).collect();
-        let number: i32 = digits.parse().unwrap();
+fn part2(values: &[Entry]) -> Result<i32, String> {
+    let display = Display::seven_segment();
 
-        sum += number;
-    }
+    values.iter().enumerate()
+        .map(|(index, entry)| {
+            let digits = decode_entry(entry, &display).map_err(|e| format!("Entry {}: {}", index + 1, e))?;
+            digits.parse::<i32>().map_err(|_| format!("Entry {}: {} is not a number", index + 1, digits))
+        })
+        .sum()
+}
 
-    sum
+fn entry_mapping(entry: &Entry, display: &Display) -> Result<Vec<usize>, DecodeError> {
+    let signals: Vec<Pattern> = entry.signals.iter().map(|signal| to_pattern(signal, display.segments)).try_collect()?;
+    deduce_mapping(&signals, &display.patterns(), display.segments)
 }
 
-fn decode_signal(signal: &str, mapping: &HashMap<char, char>) -> Result<char, String> {
-    let segments: String = signal.chars().map(|c| mapping[&c]).sorted().collect();
+fn decode_entry(entry: &Entry, display: &Display) -> Result<String, DecodeError> {
+    let mapping = entry_mapping(entry, display)?;
 
-    decode_digit(segments.as_str())
+    entry.output.iter()
+        .map(|signal| decode_signal(signal, &mapping, display))
+        .try_collect()
 }
 
-fn decode_digit(segments: &str) -> Result<char, String> {
-    match segments {
-        "abcefg" => Ok('0'),
-        "cf" => Ok('1'),
-        "acdeg" => Ok('2'),
-        "acdfg" => Ok('3'),
-        "bcdf" => Ok('4'),
-        "abdfg" => Ok('5'),
-        "abdefg" => Ok('6'),
-        "acf" => Ok('7'),
-        "abcdefg" => Ok('8'),
-        "abcdfg" => Ok('9'),
-        _ => Err(format!("Unexpected combination {}", segments))
-    }
-}
+// The wiring followed by the output as it should have appeared on the display
+fn show_entry(entry: &Entry, display: &Display) -> Result<String, DecodeError> {
+    let mapping = entry_mapping(entry, display)?;
+    let wiring = mapping.iter().enumerate()
+        .map(|(wire, segment)| format!("{}->{}", (b'a' + wire as u8) as char, (b'a' + *segment as u8) as char))
+        .join(" ");
 
-fn brute_force_signal_mapping(signals: &[String], permutations: &[HashMap<char, char>]) -> HashMap<char, char> {
-    permutations.iter().filter(|p| permutation_valid(signals, p))
-        .cloned().next().unwrap()
-}
+    let segments: Vec<Pattern> = entry.output.iter()
+        .map(|signal| Ok(apply(to_pattern(signal, display.segments)?, &mapping)))
+        .collect::<Result<_, DecodeError>>()?;
+    let art = display.render(&segments);
 
-fn permutation_valid(signals: &[String], permutation: &HashMap<char, char>) -> bool {
-    signals.iter().all(|signal| decode_signal(signal, permutation).is_ok())
+    Ok(format!("Wiring: {}\n{}Output: {}\n", wiring, art, display.read_art(&art)?))
 }
 
-fn generate_permutations() -> Vec<HashMap<char, char>> {
-    let options = vec!['a', 'b', 'c', 'd', 'e', 'f', 'g'];
+fn decode_signal(signal: &str, mapping: &[usize], display: &Display) -> Result<char, DecodeError> {
+    display.decode(apply(to_pattern(signal, display.segments)?, mapping))
+}
 
-    options.iter().permutations(7).map(|p| {
-        p.iter().enumerate().map(|e| (**e.1, options[e.0])).collect()
-    }).collect_vec()
+#[cfg(test)]
+fn decode_digit(segments: &str) -> Result<char, DecodeError> {
+    let display = Display::seven_segment();
+    display.decode(to_pattern(segments, display.segments)?)
 }
 
 #[cfg(test)]
@@ -106,7 +135,31 @@ mod tests {
         let values = parse_input("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf");
         let result = part2(&values);
 
-        assert_eq!(5353, result);
+        assert_eq!(Ok(5353), result);
+    }
+
+    #[test]
+    fn part2_should_work_for_all_example_entries() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part2(&values);
+
+        assert_eq!(Ok(61229), result);
+    }
+
+    #[test]
+    fn decode_entry_should_report_ambiguous_signals() {
+        let values = parse_input("ab dab eafb | ab ab ab ab");
+
+        assert_eq!(Err(DecodeError::Ambiguous), decode_entry(&values[0], &Display::seven_segment()));
+    }
+
+    #[test]
+    fn decode_entry_should_report_inconsistent_signals() {
+        let values = parse_input("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb abc | cdfeb fcadb cdfeb cdbaf");
+
+        assert_eq!(Err(DecodeError::Inconsistent), decode_entry(&values[0], &Display::seven_segment()));
+        assert!(part2(&values).unwrap_err().starts_with("Entry 1:"));
     }
 
     #[test]
@@ -208,16 +261,60 @@ mod tests {
              g", '9');
     }
 
+    #[test]
+    fn decode_entry_should_support_fourteen_segment_displays() {
+        let display = Display::parse(&fs::read_to_string("displays/fourteen_segment.txt").unwrap()).unwrap();
+        let wiring = "nmlkjihgfedcba";
+        let scramble = |glyphs: &str| glyphs.chars()
+            .map(|glyph| display.glyphs.iter().find(|(_, c)| *c == glyph).unwrap().0)
+            .map(|pattern| (0..14).filter(|s| pattern & (1 << s) != 0).map(|s| wiring.chars().nth(s).unwrap()).collect::<String>())
+            .join(" ");
+
+        let all: String = display.glyphs.iter().map(|(_, c)| *c).collect();
+        let values = parse_input(&format!("{} | {}", scramble(&all), scramble("HELLO2021")));
+
+        assert_eq!(Ok(String::from("HELLO2021")), decode_entry(&values[0], &display));
+    }
+
+    #[test]
+    fn display_should_reject_invalid_definitions() {
+        assert!(Display::parse("segments 3\n1 ab\n7 ab").is_err());
+        assert!(Display::parse("segments 3\n1 az").is_err());
+        assert!(Display::parse("10 ab").is_err());
+    }
+
+    #[test]
+    fn render_should_draw_segment_art() {
+        let display = Display::seven_segment();
+        let patterns = display.parse_art(&display.render(&[display.glyphs[5].0, display.glyphs[7].0])).unwrap();
+
+        assert_eq!(" aaaa    aaaa
+b    .  .    c
+b    .  .    c
+ dddd    ....
+.    f  .    f
+.    f  .    f
+ gggg    ....
+", display.render(&patterns));
+        assert_eq!(Ok(String::from("57")), display.read_art(&display.render(&patterns)));
+    }
+
+    #[test]
+    fn show_entry_should_print_wiring_and_output() {
+        let values = parse_input("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf");
+        let result = show_entry(&values[0], &Display::seven_segment()).unwrap();
+
+        assert!(result.starts_with("Wiring: a->c b->f c->g d->a e->b f->d g->e\n aaaa    aaaa    aaaa    aaaa\n"));
+        assert!(result.ends_with("Output: 5353\n"));
+    }
+
     fn test_decode_digit(input: &str, expected: char) {
         let result = decode_digit(to_signal_string(input).as_str());
         assert_eq!(expected, result.unwrap());
     }
 
     fn to_signal_string(input: &str) -> String {
-        let normalized = input
-            .replace(' ', "")
-            .replace('\r', "")
-            .replace('\n', "");
+        let normalized = input.replace([' ', '\r', '\n'], "");
 
         normalized.chars().sorted().collect()
     }
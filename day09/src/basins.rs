@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+use crate::Point;
+use crate::heightmap::Heightmap;
+
+const NO_BASIN: u32 = u32::MAX;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basin {
+    pub size: usize,
+    pub low_point: Point,
+}
+
+#[derive(Debug, Clone)]
+pub struct BasinMap {
+    width: usize,
+    height: usize,
+    // Basin id for every cell, row by row, NO_BASIN for the walls between basins
+    labels: Vec<u32>,
+    pub basins: Vec<Basin>,
+}
+
+// Parents are stored as u32 like the labels, keeping large maps at 8 bytes per cell
+struct UnionFind {
+    parents: Vec<u32>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> UnionFind {
+        assert!(size <= u32::MAX as usize, "Maps can have at most {} cells", u32::MAX);
+        UnionFind { parents: (0..size as u32).collect() }
+    }
+
+    // Path halving: every other node on the way up is pointed at its grandparent
+    fn find(&mut self, index: usize) -> usize {
+        let mut current = index;
+        while self.parents[current] as usize != current {
+            let grandparent = self.parents[self.parents[current] as usize];
+            self.parents[current] = grandparent;
+            current = grandparent as usize;
+        }
+
+        current
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b) as u32;
+        }
+    }
+}
+
+// Joins every basin cell with its basin neighbours in a single scan over the flat buffer,
+// then numbers the resulting sets in the order their first cell appears. Unions keep the
+// lowest index as the root, so a set's root is its first cell and gets the new id.
+pub fn label_basins(map: &Heightmap) -> BasinMap {
+    let mut sets = UnionFind::new(map.width * map.height);
+
+    for (x, y) in map.cells().filter(|&(x, y)| !map.is_wall(x, y)) {
+        for (nx, ny) in map.neighbours(x, y) {
+            if !map.is_wall(nx, ny) {
+                sets.union(map.index(x, y), map.index(nx, ny));
+            }
+        }
+    }
+
+    let mut labels = vec![NO_BASIN; map.width * map.height];
+    let mut basins: Vec<Basin> = vec![];
+
+    for (x, y) in map.cells().filter(|&(x, y)| !map.is_wall(x, y)) {
+        let root = sets.find(map.index(x, y));
+
+        if root == map.index(x, y) {
+            labels[root] = basins.len() as u32;
+            basins.push(Basin { size: 0, low_point: Point::new(x as i32, y as i32) });
+        }
+
+        let id = labels[root];
+        let basin = &mut basins[id as usize];
+        let low_point = &basin.low_point;
+        basin.size += 1;
+
+        if map.get(x, y) < map.get(low_point.x as usize, low_point.y as usize) {
+            basin.low_point = Point::new(x as i32, y as i32);
+        }
+
+        labels[map.index(x, y)] = id;
+    }
+
+    BasinMap { width: map.width, height: map.height, labels, basins }
+}
+
+impl BasinMap {
+    pub fn label(&self, x: usize, y: usize) -> Option<usize> {
+        match self.labels[(y * self.width) + x] {
+            NO_BASIN => None,
+            id => Some(id as usize),
+        }
+    }
+
+    // Wall cells that separate two or more different basins
+    pub fn borders(&self, map: &Heightmap) -> Vec<Point> {
+        let mut borders = vec![];
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.label(x, y).is_some() {
+                    continue;
+                }
+
+                let neighbours: HashSet<usize> = map.neighbours(x, y)
+                    .filter_map(|(nx, ny)| self.label(nx, ny))
+                    .collect();
+
+                if neighbours.len() > 1 {
+                    borders.push(Point::new(x as i32, y as i32));
+                }
+            }
+        }
+
+        borders
+    }
+}
@@ -0,0 +1,111 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    Four,
+    Eight,
+}
+
+// What lies beyond the edge of the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    // Nothing, cells on the edge simply have fewer neighbours
+    Wall,
+    // The opposite side of the map
+    Wrap,
+    // The nearest cell on the edge itself
+    Clamp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    // Cells at or above this height separate basins
+    pub wall_height: u8,
+    pub neighbourhood: Neighbourhood,
+    pub edge: Edge,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules { wall_height: 9, neighbourhood: Neighbourhood::Four, edge: Edge::Wall }
+    }
+}
+
+const FOUR: [(i64, i64); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+const EIGHT: [(i64, i64); 8] = [(0, -1), (0, 1), (-1, 0), (1, 0), (-1, -1), (1, -1), (-1, 1), (1, 1)];
+
+// Heights stored row by row in a single buffer, one byte per cell
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    pub width: usize,
+    pub height: usize,
+    pub rules: Rules,
+    data: Vec<u8>,
+}
+
+impl Heightmap {
+    pub fn parse(input: &str, rules: Rules) -> Heightmap {
+        let mut data = Vec::with_capacity(input.len());
+        let mut width = 0;
+        let mut height = 0;
+
+        for line in input.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let row_start = data.len();
+            data.extend(line.bytes().map(|b| match b {
+                b'0'..=b'9' => b - b'0',
+                _ => panic!("Unexpected height {}", b as char),
+            }));
+
+            if height > 0 && data.len() - row_start != width {
+                panic!("Row {} has {} cells instead of {}", height, data.len() - row_start, width);
+            }
+
+            width = data.len() - row_start;
+            height += 1;
+        }
+
+        Heightmap { width, height, rules, data }
+    }
+
+    pub fn with_rules(self, rules: Rules) -> Heightmap {
+        Heightmap { rules, ..self }
+    }
+
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        (y * self.width) + x
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.data[self.index(x, y)]
+    }
+
+    pub fn is_wall(&self, x: usize, y: usize) -> bool {
+        self.get(x, y) >= self.rules.wall_height
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    fn resolve(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+
+        match self.rules.edge {
+            Edge::Wall if x < 0 || y < 0 || x >= width || y >= height => None,
+            Edge::Wall => Some((x as usize, y as usize)),
+            Edge::Wrap => Some((x.rem_euclid(width) as usize, y.rem_euclid(height) as usize)),
+            Edge::Clamp => Some((x.clamp(0, width - 1) as usize, y.clamp(0, height - 1) as usize)),
+        }
+    }
+
+    // Neighbours that resolve back onto the cell itself are left out
+    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let offsets: &[(i64, i64)] = match self.rules.neighbourhood {
+            Neighbourhood::Four => &FOUR,
+            Neighbourhood::Eight => &EIGHT,
+        };
+
+        offsets.iter()
+            .filter_map(move |(dx, dy)| self.resolve(x as i64 + dx, y as i64 + dy))
+            .filter(move |neighbour| *neighbour != (x, y))
+    }
+}
@@ -1,6 +1,12 @@
-use std::{fs, collections::HashSet};
+use std::{env, fs};
+use basins::label_basins;
+use heightmap::{Edge, Heightmap, Neighbourhood, Rules};
 use itertools::Itertools;
 
+mod basins;
+mod heightmap;
+mod render;
+
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 struct Point {
     x: i32,
@@ -15,7 +21,31 @@ impl Point {
 
 fn main() {
     let input = fs::read_to_string("input.txt").unwrap();
-    let values = parse_input(&input);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let values = parse_input(&input).with_rules(parse_rules(&args).unwrap());
+
+    if args.iter().any(|arg| arg == "--basins") {
+        let map = label_basins(&values);
+
+        for (id, basin) in map.basins.iter().enumerate() {
+            println!("Basin {}: size {}, low point {},{}", id, basin.size, basin.low_point.x, basin.low_point.y);
+        }
+
+        let borders = map.borders(&values).iter().map(|p| format!("{},{}", p.x, p.y)).join(" ");
+        println!("Borders: {}", borders);
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--show") {
+        print!("{}", render::render_terminal(&values, &label_basins(&values)));
+        return;
+    }
+
+    if let Some(path) = option_value(&args, "--image") {
+        let scale = option_value(&args, "--scale").map(|s| s.parse().unwrap()).unwrap_or(4);
+        fs::write(path, render::render_ppm(&values, &label_basins(&values), scale)).unwrap();
+        return;
+    }
 
     let part1 = part1(&values);
     let part2 = part2(&values);
@@ -23,86 +53,55 @@ fn main() {
     println!("Part 1: {} Part 2: {}", part1, part2);
 }
 
-fn parse_input(input: &str) -> Vec<Vec<i32>> {
-    input.lines().map(|line| line.trim().chars().map(|c| c.to_string().parse().unwrap()).collect_vec()).collect()
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .map(|value| value.as_str())
 }
 
-fn part1(values: &[Vec<i32>]) -> i32 {
-    find_low_points(values).iter().map(|p| values[p.y as usize][p.x as usize] + 1).sum()
-}
+fn parse_rules(args: &[String]) -> Result<Rules, String> {
+    let mut rules = Rules::default();
 
-fn part2(values: &[Vec<i32>]) -> i32 {
-    find_low_points(values).iter()
-        .map(|p| get_basin_size(p, values))
-        .sorted()
-        .rev()
-        .take(3)
-        .product()
-}
-
-fn find_low_points(values: &[Vec<i32>]) -> Vec<Point> {
-    let mut result = Vec::new();
-
-    for cell in all_grid_cells(values[0].len(), values.len()) {
-        let value = get_cell(&cell, values);
-        let neighbours = get_neighbours(&cell, values);
+    if let Some(height) = option_value(args, "--wall") {
+        rules.wall_height = height.parse().map_err(|_| format!("Invalid wall height {}", height))?;
+    }
 
-        if neighbours.iter().all(|neighbour| *neighbour > value) {
-            result.push(cell.clone());
-        }
+    if args.iter().any(|arg| arg == "--diagonal") {
+        rules.neighbourhood = Neighbourhood::Eight;
     }
 
-    result
-}
+    rules.edge = match option_value(args, "--edge") {
+        None | Some("wall") => Edge::Wall,
+        Some("wrap") => Edge::Wrap,
+        Some("clamp") => Edge::Clamp,
+        Some(edge) => return Err(format!("Unknown edge behaviour {}", edge)),
+    };
 
-fn all_grid_cells(width: usize, height: usize) -> Vec<Point> {
-    let mut points = Vec::new();
-    for y in 0..height {
-        for x in 0..width {
-            points.push(Point::new(x as i32, y as i32));
-        }
-    }
-    points
+    Ok(rules)
 }
 
-fn get_neighbours(point: &Point, values: &[Vec<i32>]) -> Vec<i32> {
-    get_neighbour_cells(point).iter().map(|p| get_cell(p, values)).collect_vec()
+fn parse_input(input: &str) -> Heightmap {
+    Heightmap::parse(input, Rules::default())
 }
 
-fn get_neighbour_cells(p: &Point) -> Vec<Point> {
-    vec![
-        Point {x: p.x, y: p.y - 1},
-        Point {x: p.x, y: p.y + 1},
-        Point {x: p.x - 1, y: p.y},
-        Point {x: p.x + 1, y: p.y}]
+fn part1(values: &Heightmap) -> usize {
+    find_low_points(values).iter().map(|p| values.get(p.x as usize, p.y as usize) as usize + 1).sum()
 }
 
-fn get_cell(point: &Point, values: &[Vec<i32>]) -> i32 {
-    if point.x >= 0 && point.x < values[0].len() as i32 && point.y >= 0 && point.y < values.len() as i32 {
-        values[point.y as usize][point.x as usize]
-    } else {
-        i32::MAX
-    }
+fn part2(values: &Heightmap) -> usize {
+    label_basins(values).basins.iter()
+        .map(|basin| basin.size)
+        .sorted()
+        .rev()
+        .take(3)
+        .product()
 }
 
-fn get_basin_size(point: &Point, values: &[Vec<i32>]) -> i32 {
-    let mut open = vec![point.clone()];
-    let mut closed: HashSet<Point> = HashSet::new();
-    let mut count = 0;
-
-    while !open.is_empty() {
-        let point = open.pop().unwrap();
-        closed.insert(point.clone());
-        count += 1;
-
-        for neighbour in get_neighbour_cells(&point).iter() {
-            if !closed.contains(neighbour) && !open.contains(neighbour) && get_cell(neighbour, values) < 9 {
-                open.push(neighbour.clone());
-            }
-        }
-    }
-
-    count
+fn find_low_points(values: &Heightmap) -> Vec<Point> {
+    values.cells()
+        .filter(|(x, y)| values.neighbours(*x, *y).all(|(nx, ny)| values.get(nx, ny) > values.get(*x, *y)))
+        .map(|(x, y)| Point::new(x as i32, y as i32))
+        .collect()
 }
 
 #[cfg(test)]
@@ -126,4 +125,74 @@ mod tests {
 
         assert_eq!(1134, result);
     }
+
+    #[test]
+    fn label_basins_should_find_sizes_and_low_points() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let map = label_basins(&values);
+
+        assert_eq!(vec![3, 9, 14, 9], map.basins.iter().map(|b| b.size).collect_vec());
+        assert_eq!(find_low_points(&values).iter().sorted_by_key(|p| (p.y, p.x)).collect_vec(),
+            map.basins.iter().map(|b| &b.low_point).sorted_by_key(|p| (p.y, p.x)).collect_vec());
+    }
+
+    #[test]
+    fn borders_should_separate_basins() {
+        let values = parse_input("12\n99\n21");
+        let map = label_basins(&values);
+
+        assert_eq!(2, map.basins.len());
+        assert_eq!(vec![Point::new(0, 1), Point::new(1, 1)], map.borders(&values));
+    }
+
+    #[test]
+    fn rules_should_change_neighbourhood_edges_and_walls() {
+        let input = "1991\n9999\n9929";
+        let rules = |args: &[&str]| parse_rules(&args.iter().map(|a| a.to_string()).collect_vec()).unwrap();
+
+        let walled = Heightmap::parse(input, rules(&[]));
+        let wrapped = Heightmap::parse(input, rules(&["--edge", "wrap"]));
+        let diagonal = Heightmap::parse(input, rules(&["--edge", "wrap", "--diagonal"]));
+        let low_walls = Heightmap::parse(input, rules(&["--wall", "2"]));
+
+        assert_eq!(vec![1, 1, 1], label_basins(&walled).basins.iter().map(|b| b.size).collect_vec());
+        assert_eq!(vec![2, 1], label_basins(&wrapped).basins.iter().map(|b| b.size).collect_vec());
+        assert_eq!(vec![3], label_basins(&diagonal).basins.iter().map(|b| b.size).collect_vec());
+        assert_eq!(vec![1, 1], label_basins(&low_walls).basins.iter().map(|b| b.size).collect_vec());
+    }
+
+    #[test]
+    fn clamped_edges_should_not_count_the_cell_itself() {
+        let clamped = Heightmap::parse("12\n34", Rules { edge: Edge::Clamp, ..Rules::default() });
+
+        assert_eq!(vec![Point::new(0, 0)], find_low_points(&clamped));
+    }
+
+    #[test]
+    fn large_maps_should_be_labelled() {
+        let row = "0129".repeat(100);
+        let input = vec![row.as_str(); 500].join("\n");
+        let map = label_basins(&parse_input(&input));
+
+        assert_eq!(100, map.basins.len());
+        assert!(map.basins.iter().all(|basin| basin.size == 1500));
+    }
+
+    #[test]
+    fn render_should_mark_the_largest_basins() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let map = label_basins(&values);
+
+        assert_eq!(vec![2, 1, 3], render::largest_basins(&map, 3));
+
+        let terminal = render::render_terminal(&values, &map);
+        assert_eq!(4, terminal.matches("\x1b[1;97m").count());
+        assert!(terminal.ends_with("#3: basin 3 with size 9, low point 6,4\n"));
+
+        let image = render::render_ppm(&values, &map, 2);
+        assert!(image.starts_with(b"P6\n20 10\n255\n"));
+        assert_eq!(13 + (20 * 10 * 3), image.len());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+use itertools::Itertools;
+use crate::basins::BasinMap;
+use crate::heightmap::Heightmap;
+
+const PALETTE: [[u8; 3]; 12] = [
+    [230, 25, 75], [60, 180, 75], [255, 225, 25], [0, 130, 200],
+    [245, 130, 48], [145, 30, 180], [70, 240, 240], [240, 50, 230],
+    [210, 245, 60], [250, 190, 212], [0, 128, 128], [170, 110, 40],
+];
+
+const WALL: [u8; 3] = [40, 40, 40];
+const LOW_POINT: [u8; 3] = [255, 255, 255];
+
+pub fn largest_basins(basins: &BasinMap, count: usize) -> Vec<usize> {
+    (0..basins.basins.len())
+        .sorted_by_key(|id| std::cmp::Reverse(basins.basins[*id].size))
+        .take(count)
+        .collect()
+}
+
+fn is_low_point(basins: &BasinMap, id: usize, x: usize, y: usize) -> bool {
+    let low_point = &basins.basins[id].low_point;
+    low_point.x as usize == x && low_point.y as usize == y
+}
+
+// Basins other than the largest ones are drawn at half brightness
+fn colour(basins: &BasinMap, largest: &[usize], x: usize, y: usize) -> [u8; 3] {
+    match basins.label(x, y) {
+        None => WALL,
+        Some(id) if is_low_point(basins, id, x, y) => LOW_POINT,
+        Some(id) if largest.contains(&id) => PALETTE[id % PALETTE.len()],
+        Some(id) => PALETTE[id % PALETTE.len()].map(|c| c / 2),
+    }
+}
+
+// Heights in the colour of their basin, the largest basins on a coloured background
+// and low points in bold white
+pub fn render_terminal(map: &Heightmap, basins: &BasinMap) -> String {
+    let largest = largest_basins(basins, 3);
+    let mut output = String::new();
+
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let [r, g, b] = colour(basins, &largest, x, y);
+            let style = match basins.label(x, y) {
+                Some(id) if is_low_point(basins, id, x, y) => String::from("\x1b[1;97m"),
+                Some(id) if largest.contains(&id) => format!("\x1b[30;48;2;{};{};{}m", r, g, b),
+                _ => format!("\x1b[38;2;{};{};{}m", r, g, b),
+            };
+
+            output.push_str(&format!("{}{}\x1b[0m", style, map.get(x, y)));
+        }
+        output.push('\n');
+    }
+
+    for (rank, id) in largest.iter().enumerate() {
+        let basin = &basins.basins[*id];
+        output.push_str(&format!("#{}: basin {} with size {}, low point {},{}\n",
+            rank + 1, id, basin.size, basin.low_point.x, basin.low_point.y));
+    }
+
+    output
+}
+
+pub fn render_ppm(map: &Heightmap, basins: &BasinMap, scale: usize) -> Vec<u8> {
+    let largest = largest_basins(basins, 3);
+    let mut data = format!("P6\n{} {}\n255\n", map.width * scale, map.height * scale).into_bytes();
+
+    for y in 0..map.height {
+        let row = (0..map.width).map(|x| colour(basins, &largest, x, y)).collect_vec();
+
+        for _ in 0..scale {
+            for pixel in row.iter() {
+                for _ in 0..scale {
+                    data.extend_from_slice(pixel);
+                }
+            }
+        }
+    }
+
+    data
+}
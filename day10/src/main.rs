@@ -1,84 +1,72 @@
+use std::env;
 use std::fs;
 use itertools::Itertools;
+use syntax::{Line, Others, Syntax};
 
-enum Line {
-    Corrupt{last_char: char},
-    Incomplete{stack: Vec<char>}
-}
+mod syntax;
 
 fn main() {
-    let input = fs::read_to_string("input.txt").unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let input = fs::read_to_string(option_value(&args, "--input").unwrap_or("input.txt")).unwrap();
     let values = parse_input(&input);
 
-    let part1 = part1(&values);
-    let part2 = part2(&values);
-
-    println!("Part 1: {} Part 2: {}", part1, part2);
-}
-
-fn parse_input(input: &str) -> Vec<String> {
-    input.lines().map(String::from).collect()
-}
-
-fn part1(values: &[String]) -> u32 {
-    analyse_lines(values).iter().map(|l| match l {
-        Line::Corrupt { last_char} => get_corrupt_char_score(*last_char),
-        _ => 0
-    }).sum()
-}
-
-fn analyse_lines(values: &[String]) -> Vec<Line> {
-    let mut result = Vec::new();
-
-    'lineloop: for line in values {
-        let mut stack = Vec::new();
+    let mut syntax = match option_value(&args, "--pairs") {
+        Some(spec) => Syntax::parse(spec).unwrap_or_else(|e| panic!("{}", e)),
+        None => Syntax::standard(),
+    };
 
-        for char in line.chars() {
-            if "([{<".contains(char) {
-                stack.push(char);
-            } else {
-                let open = stack.pop().unwrap();
+    if args.iter().any(|arg| arg == "--ignore-other") {
+        syntax.others = Others::Ignore;
+    }
 
-                if !equal_type(open, char) {
-                    result.push(Line::Corrupt{ last_char: char});
-                    continue 'lineloop;
+    if args.iter().any(|arg| arg == "--fix") {
+        for (number, line) in values.iter().enumerate() {
+            match syntax.fix(line) {
+                Some(fixed) => println!("{}", fixed),
+                None => {
+                    eprintln!("{}: {} (no single character repair)", number + 1, syntax.check(line));
+                    println!("{}", line);
                 }
             }
         }
+        return;
+    }
 
-        if !stack.is_empty() {
-            result.push(Line::Incomplete { stack });
+    if args.iter().any(|arg| arg == "--report") {
+        for (number, line) in values.iter().enumerate() {
+            println!("{}: {}", number + 1, syntax.check(line));
         }
+        return;
     }
 
-    result
+    let part1 = part1(&values, &syntax);
+    let part2 = part2(&values, &syntax);
+
+    println!("Part 1: {} Part 2: {}", part1, part2);
 }
 
-fn equal_type(c1: char, c2: char) -> bool {
-    match (c1, c2) {
-        ('(', ')') => true,
-        ('{', '}') => true,
-        ('[', ']') => true,
-        ('<', '>') => true,
-        _ => false
-    }
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).map(String::as_str)
 }
 
-fn get_corrupt_char_score(c: char) -> u32 {
-    match c {
-        ')' => 3,
-        '}' => 57,
-        ']' => 1197,
-        '>' => 25137,
-        _ => panic!()
-    }
+fn parse_input(input: &str) -> Vec<String> {
+    input.lines().map(String::from).collect()
 }
 
-fn part2(values: &[String]) -> u64 {
-    let scores = analyse_lines(values).iter()
+fn part1(values: &[String], syntax: &Syntax) -> u64 {
+    analyse_lines(values, syntax).iter().map(|l| syntax.corrupt_score(l)).sum()
+}
+
+fn analyse_lines(values: &[String], syntax: &Syntax) -> Vec<Line> {
+    values.iter().map(|line| syntax.check(line)).collect()
+}
+
+fn part2(values: &[String], syntax: &Syntax) -> u64 {
+    let scores = analyse_lines(values, syntax).iter()
         .filter_map(|line| {
             if let Line::Incomplete{stack} = line {
-                Some(calculate_incomplete_line_score(stack))
+                Some(syntax.completion_score(stack))
             } else {
                 None
             }
@@ -87,31 +75,16 @@ fn part2(values: &[String]) -> u64 {
     *scores.iter().sorted().nth(scores.len() / 2).unwrap()
 }
 
-fn calculate_incomplete_line_score(stack: &Vec<char>) -> u64 {
-    stack.iter().rev()
-        .map(|c| get_missing_char_score(*c))
-        .fold(0, |acc, value| (acc * 5) + value)
-}
-
-fn get_missing_char_score(c: char) -> u64 {
-    match c {
-        '(' => 1,
-        '{' => 3,
-        '[' => 2,
-        '<' => 4,
-        _ => panic!()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use syntax::Edit;
 
     #[test]
     fn part1_should_work() {
         let input = fs::read_to_string("input2.txt").unwrap();
         let values = parse_input(&input);
-        let result = part1(&values);
+        let result = part1(&values, &Syntax::standard());
 
         assert_eq!(26397, result);
     }
@@ -120,8 +93,59 @@ mod tests {
     fn part2_should_work() {
         let input = fs::read_to_string("input2.txt").unwrap();
         let values = parse_input(&input);
-        let result = part2(&values);
+        let result = part2(&values, &Syntax::standard());
 
         assert_eq!(288957, result);
     }
+
+    #[test]
+    fn check_should_report_the_column_of_the_first_error() {
+        let syntax = Syntax::standard();
+
+        assert_eq!(Line::Corrupt { column: 13, expected: Some(']'), found: '}' }, syntax.check("{([(<{}[<>[]}>{[]{[(<()>"));
+        assert_eq!(Line::Corrupt { column: 1, expected: None, found: ')' }, syntax.check(")("));
+        assert_eq!(Line::Invalid { column: 3, found: 'x' }, syntax.check("[(x)]"));
+        assert_eq!(Line::Valid, syntax.check("[<>({}){}[([])<>]]"));
+        assert_eq!(Line::Incomplete { stack: vec![1, 0] }, syntax.check("[(<>"));
+    }
+
+    #[test]
+    fn custom_pairs_should_be_used() {
+        let syntax = Syntax::parse("()=1:1,/\\=5:2").unwrap();
+
+        assert_eq!(Line::Invalid { column: 1, found: '[' }, syntax.check("[]"));
+        assert_eq!(5, syntax.corrupt_score(&syntax.check("(\\")));
+        assert_eq!(11, syntax.completion_score(&[0, 1]));
+        assert!(Syntax::parse("()=1:1,)]=2:2").is_err());
+        assert!(Syntax::parse("()=1").is_err());
+    }
+
+    #[test]
+    fn completion_should_close_open_brackets() {
+        let syntax = Syntax::standard();
+
+        assert_eq!(Line::Incomplete { stack: vec![1, 0, 2, 0, 1, 1, 2, 2] }, syntax.check("[({(<(())[]>[[{[]{<()<>>"));
+        assert_eq!("}}]])})]", syntax.completion(&[1, 0, 2, 0, 1, 1, 2, 2]));
+    }
+
+    #[test]
+    fn repair_should_find_a_single_character_edit() {
+        let syntax = Syntax::standard();
+
+        assert_eq!(Some(Edit::Replace { column: 5, with: ')' }), syntax.repair("[(<>]]"));
+        assert_eq!(Some(Edit::Delete { column: 1 }), syntax.repair(")()"));
+        assert_eq!(Some(Edit::Delete { column: 2 }), syntax.repair("(x)"));
+        assert_eq!(None, syntax.repair("(())"));
+        assert_eq!(Some(String::from("[(<>)]")), syntax.fix("[(<>]]"));
+        assert_eq!(Some(String::from("{()}")), syntax.fix("{("));
+        assert_eq!(None, syntax.fix(")))((("));
+    }
+
+    #[test]
+    fn ignored_characters_should_be_skipped() {
+        let mut syntax = Syntax::standard();
+        syntax.others = Others::Ignore;
+
+        assert_eq!(Some(String::from("fn main() { println!(\"{}\", x[0]); }")), syntax.fix("fn main() { println!(\"{}\", x[0]); "));
+    }
 }
\ No newline at end of file
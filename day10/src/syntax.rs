@@ -0,0 +1,210 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pair {
+    pub open: char,
+    pub close: char,
+    pub corrupt_score: u64,
+    pub missing_score: u64,
+}
+
+// What to do with characters that are not part of any pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Others {
+    Reject,
+    Ignore,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Syntax {
+    pub pairs: Vec<Pair>,
+    pub others: Others,
+}
+
+// Columns are 1-based, like in an editor
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    Valid,
+    Corrupt { column: usize, expected: Option<char>, found: char },
+    Invalid { column: usize, found: char },
+    Incomplete { stack: Vec<usize> },
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Valid => write!(f, "valid"),
+            Line::Corrupt { column, expected: Some(expected), found } =>
+                write!(f, "column {}: expected '{}' but found '{}'", column, expected, found),
+            Line::Corrupt { column, expected: None, found } =>
+                write!(f, "column {}: unexpected '{}' without an opening bracket", column, found),
+            Line::Invalid { column, found } =>
+                write!(f, "column {}: '{}' is not a bracket", column, found),
+            Line::Incomplete { stack } => write!(f, "incomplete, {} bracket(s) left open", stack.len()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Edit {
+    Replace { column: usize, with: char },
+    Insert { column: usize, with: char },
+    Delete { column: usize },
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Edit::Replace { column, with } => write!(f, "replace column {} with '{}'", column, with),
+            Edit::Insert { column, with } => write!(f, "insert '{}' at column {}", with, column),
+            Edit::Delete { column } => write!(f, "delete column {}", column),
+        }
+    }
+}
+
+impl Edit {
+    fn apply(&self, line: &[char]) -> String {
+        let mut result = line.to_vec();
+
+        match self {
+            Edit::Replace { column, with } => result[column - 1] = *with,
+            Edit::Insert { column, with } => result.insert(column - 1, *with),
+            Edit::Delete { column } => { result.remove(column - 1); },
+        }
+
+        result.into_iter().collect()
+    }
+}
+
+impl Syntax {
+    pub fn standard() -> Syntax {
+        Syntax::parse("()=3:1,[]=1197:2,{}=57:3,<>=25137:4").unwrap()
+    }
+
+    // Pairs are written as "<open><close>=<corrupt score>:<missing score>", separated by commas
+    pub fn parse(spec: &str) -> Result<Syntax, String> {
+        let pairs = spec.split(',')
+            .map(|pair| {
+                let (brackets, scores) = pair.trim().split_once('=')
+                    .ok_or_else(|| format!("Missing scores in pair '{}'", pair))?;
+                let (corrupt, missing) = scores.split_once(':')
+                    .ok_or_else(|| format!("Expected <corrupt>:<missing> scores in pair '{}'", pair))?;
+                let chars: Vec<char> = brackets.chars().collect();
+
+                if chars.len() != 2 || chars[0] == chars[1] {
+                    return Err(format!("Expected two different brackets in pair '{}'", pair));
+                }
+
+                Ok(Pair {
+                    open: chars[0],
+                    close: chars[1],
+                    corrupt_score: corrupt.parse().map_err(|_| format!("Invalid score '{}'", corrupt))?,
+                    missing_score: missing.parse().map_err(|_| format!("Invalid score '{}'", missing))?,
+                })
+            })
+            .collect::<Result<Vec<Pair>, String>>()?;
+
+        let mut brackets: Vec<char> = pairs.iter().flat_map(|p| [p.open, p.close]).collect();
+        brackets.sort_unstable();
+        brackets.dedup();
+
+        if brackets.len() != pairs.len() * 2 {
+            return Err(String::from("A bracket can only be used in one pair"));
+        }
+
+        Ok(Syntax { pairs, others: Others::Reject })
+    }
+
+    fn opening(&self, c: char) -> Option<usize> {
+        self.pairs.iter().position(|p| p.open == c)
+    }
+
+    fn closing(&self, c: char) -> Option<usize> {
+        self.pairs.iter().position(|p| p.close == c)
+    }
+
+    pub fn check(&self, line: &str) -> Line {
+        let mut stack = Vec::new();
+
+        for (index, c) in line.chars().enumerate() {
+            let column = index + 1;
+
+            if let Some(pair) = self.opening(c) {
+                stack.push(pair);
+            } else if let Some(pair) = self.closing(c) {
+                match stack.pop() {
+                    Some(open) if open == pair => {},
+                    open => return Line::Corrupt { column, expected: open.map(|o| self.pairs[o].close), found: c },
+                }
+            } else if self.others == Others::Reject {
+                return Line::Invalid { column, found: c };
+            }
+        }
+
+        if stack.is_empty() {
+            Line::Valid
+        } else {
+            Line::Incomplete { stack }
+        }
+    }
+
+    pub fn corrupt_score(&self, line: &Line) -> u64 {
+        match line {
+            Line::Corrupt { found, .. } => self.closing(*found).map_or(0, |p| self.pairs[p].corrupt_score),
+            _ => 0,
+        }
+    }
+
+    pub fn completion_score(&self, stack: &[usize]) -> u64 {
+        stack.iter().rev()
+            .map(|pair| self.pairs[*pair].missing_score)
+            .fold(0, |acc, value| (acc * 5) + value)
+    }
+
+    pub fn completion(&self, stack: &[usize]) -> String {
+        stack.iter().rev().map(|pair| self.pairs[*pair].close).collect()
+    }
+
+    // Finds a single character edit after which the line is no longer corrupt. Edits are tried
+    // from the error back towards the start of the line, preferring the expected closing bracket at
+    // the error, then deleting the character and then the other brackets.
+    pub fn repair(&self, line: &str) -> Option<Edit> {
+        let (error_column, expected) = match self.check(line) {
+            Line::Corrupt { column, expected, .. } => (column, expected),
+            Line::Invalid { column, .. } => (column, None),
+            _ => return None,
+        };
+
+        let chars: Vec<char> = line.chars().collect();
+        let brackets: Vec<char> = self.pairs.iter().flat_map(|p| [p.close, p.open]).collect();
+
+        (1..=error_column).rev()
+            .flat_map(|column| {
+                let mut edits = Vec::new();
+
+                if let Some(with) = expected.filter(|_| column == error_column) {
+                    edits.push(Edit::Replace { column, with });
+                }
+
+                edits.push(Edit::Delete { column });
+                edits.extend(brackets.iter().map(|with| Edit::Replace { column, with: *with }));
+                edits.extend(brackets.iter().map(|with| Edit::Insert { column, with: *with }));
+                edits
+            })
+            .find(|edit| matches!(self.check(&edit.apply(&chars)), Line::Valid | Line::Incomplete { .. }))
+    }
+
+    // The line after repairing it and completing any brackets left open
+    pub fn fix(&self, line: &str) -> Option<String> {
+        let repaired = match self.repair(line) {
+            Some(edit) => edit.apply(&line.chars().collect::<Vec<char>>()),
+            None => line.to_string(),
+        };
+
+        match self.check(&repaired) {
+            Line::Valid => Some(repaired),
+            Line::Incomplete { stack } => Some(repaired + &self.completion(&stack)),
+            _ => None,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use crate::simulate_step;
+
+#[derive(Debug, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+    pub flashes_per_cycle: usize,
+}
+
+pub struct History {
+    pub cycle: Cycle,
+    // Total number of flashes after each step, starting with 0 before the first step
+    pub totals: Vec<usize>,
+}
+
+impl History {
+    pub fn flashes_after(&self, steps: u64) -> u64 {
+        let start = self.cycle.start as u64;
+
+        if steps < start {
+            return self.totals[steps as usize] as u64;
+        }
+
+        let cycles = (steps - start) / self.cycle.period as u64;
+        let remainder = ((steps - start) % self.cycle.period as u64) as usize;
+
+        self.totals[self.cycle.start + remainder] as u64 + cycles * self.cycle.flashes_per_cycle as u64
+    }
+}
+
+// Simulates until the grid returns to a state it has been in before. Grids that synchronise
+// repeat quickly, but others can take far too long, so this gives up after max_steps.
+pub fn find_cycle(values: &[Vec<i32>], max_steps: usize) -> Option<History> {
+    let mut values = values.to_vec();
+    let mut seen = HashMap::new();
+    let mut totals = vec![0];
+
+    loop {
+        let step = totals.len() - 1;
+
+        if let Some(start) = seen.insert(values.clone(), step) {
+            let cycle = Cycle {
+                start,
+                period: step - start,
+                flashes_per_cycle: totals[step] - totals[start],
+            };
+
+            return Some(History { cycle, totals });
+        }
+
+        if step == max_steps {
+            return None;
+        }
+
+        let flashes = simulate_step(&mut values).len();
+        totals.push(totals[step] + flashes);
+    }
+}
@@ -1,6 +1,9 @@
-use std::{fs, collections::HashSet};
+use std::{env, fs, thread, collections::VecDeque, path::Path, time::Duration};
 use itertools::Itertools;
 
+mod cycle;
+mod render;
+
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
 struct Point {
     x: i32,
@@ -13,14 +16,67 @@ impl Point {
     }
 }
 
+// Every state is kept while looking for a cycle, so the search has to stop somewhere
+const MAX_CYCLE_STEPS: usize = 10_000;
+
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
     let input = fs::read_to_string("input.txt").unwrap();
     let values = parse_input(&input);
 
+    let max_steps = option_value(&args, "--max-steps").map_or(MAX_CYCLE_STEPS, |m| m.parse().unwrap());
+
+    if args.iter().any(|arg| arg == "--cycle") {
+        match cycle::find_cycle(&values, max_steps) {
+            Some(history) => println!("Cycle starts at step {} with a period of {} and {} flashes per cycle",
+                history.cycle.start, history.cycle.period, history.cycle.flashes_per_cycle),
+            None => println!("No cycle within {} steps", max_steps),
+        }
+        return;
+    }
+
+    let frames = option_value(&args, "--frames").map_or(100, |f| f.parse().unwrap());
+
+    if args.iter().any(|arg| arg == "--animate") {
+        let delay = option_value(&args, "--delay").map_or(100, |d| d.parse().unwrap());
+        print!("\x1b[2J");
+        animate(&values, frames, |step, values, flashed| {
+            print!("{}", render::render_terminal(values, flashed, step));
+            thread::sleep(Duration::from_millis(delay));
+        });
+        return;
+    }
+
+    if let Some(directory) = option_value(&args, "--frames-dir") {
+        fs::create_dir_all(directory).unwrap();
+        animate(&values, frames, |step, values, flashed| {
+            let path = Path::new(directory).join(format!("frame_{:04}.ppm", step));
+            fs::write(path, render::render_ppm(values, flashed, 8)).unwrap();
+        });
+        return;
+    }
+
+    if let Some(steps) = option_value(&args, "--steps") {
+        let steps = steps.parse().unwrap();
+        match cycle::find_cycle(&values, max_steps) {
+            Some(history) => println!("Flashes after {} steps: {}", steps, history.flashes_after(steps)),
+            None => println!("No cycle within {} steps", max_steps),
+        }
+        return;
+    }
+
     let part1 = part1(&values, 100);
-    let part2 = part2(&values);
+    let part2 = part2(&values, max_steps);
+
+    match part2 {
+        Ok(part2) => println!("Part 1: {} Part 2: {}", part1, part2),
+        Err(e) => println!("Part 1: {} Part 2: {}", part1, e),
+    }
+}
 
-    println!("Part 1: {} Part 2: {}", part1, part2);
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).map(String::as_str)
 }
 
 fn parse_input(input: &str) -> Vec<Vec<i32>> {
@@ -34,60 +90,72 @@ fn part1(values: &[Vec<i32>], steps: i32) -> usize {
     let mut flashes = 0;
 
     for _ in 0..steps {
-        flashes += simulate_step(&mut values);
+        flashes += simulate_step(&mut values).len();
     }
     
     flashes
 }
 
-fn part2(values: &[Vec<i32>]) -> i32 {
-    let mut values = values.iter().cloned().collect_vec();
-    let mut step = 0;
+// The history runs until the grid repeats, so a step where every cell flashes is either in it
+// or never happens
+fn part2(values: &[Vec<i32>], max_steps: usize) -> Result<usize, String> {
+    let history = cycle::find_cycle(values, max_steps).ok_or_else(|| format!("No cycle within {} steps", max_steps))?;
 
-    loop {
-        step += 1;
-        let flashes = simulate_step(&mut values);
-        if all_cells_flashed(flashes, &values) {
-            break;
-        }
-    }
+    history.totals.windows(2)
+        .position(|totals| all_cells_flashed(totals[1] - totals[0], values))
+        .map(|step| step + 1)
+        .ok_or_else(|| format!("The octopuses never flash at the same time, the grid repeats every {} steps from step {}",
+            history.cycle.period, history.cycle.start))
+}
 
-    step
+// Calls the callback with the initial grid and then after every step with the cells that flashed
+fn animate<F>(values: &[Vec<i32>], steps: usize, mut frame: F)
+    where F: FnMut(usize, &[Vec<i32>], &[Point]) {
+    let mut values = values.to_vec();
+    frame(0, &values, &[]);
+
+    for step in 1..=steps {
+        let flashed = simulate_step(&mut values);
+        frame(step, &values, &flashed);
+    }
 }
 
 fn all_cells_flashed(flashes: usize, values: &[Vec<i32>]) -> bool {
     flashes == values.len() * values[0].len()
 }
 
-fn simulate_step(values: &mut Vec<Vec<i32>>) -> usize {
+// Returns the cells that flashed during the step
+fn simulate_step(values: &mut [Vec<i32>]) -> Vec<Point> {
     increase_energy(values);
-    let flashes = process_flashes(values);
-
-    flashes.len()
+    process_flashes(values)
 }
 
-fn increase_energy(values: &mut Vec<Vec<i32>>) {
+fn increase_energy(values: &mut [Vec<i32>]) {
     for cell in all_cells(values[0].len(), values.len()) {
         values[cell.y as usize][cell.x as usize] += 1;
     }
 }
 
-fn process_flashes(values: &mut Vec<Vec<i32>>) -> HashSet<Point>{
-    let mut flashed = HashSet::new();
+// Cells are queued the moment their energy passes 9, so each one flashes exactly once
+fn process_flashes(values: &mut [Vec<i32>]) -> Vec<Point> {
+    let mut queue: VecDeque<Point> = all_cells(values[0].len(), values.len())
+        .filter(|cell| get(cell, values) > 9)
+        .collect();
+    let mut flashed = Vec::new();
 
-    loop {
-        let mut any_flash = false;
-        
-        for cell in all_cells(values[0].len(), values.len()) {
-            if get(&cell, values) > 9 && !flashed.contains(&cell) {
-                flashed.insert(cell);
-                flash(&cell, values);
-                any_flash = true;
+    while let Some(cell) = queue.pop_front() {
+        flashed.push(cell);
+
+        for neighbour in get_neighbours(cell) {
+            if !in_range(&neighbour, values) {
+                continue;
             }
-        }
 
-        if !any_flash {
-            break;
+            increase(&neighbour, values);
+
+            if get(&neighbour, values) == 10 {
+                queue.push_back(neighbour);
+            }
         }
     }
 
@@ -98,12 +166,6 @@ fn process_flashes(values: &mut Vec<Vec<i32>>) -> HashSet<Point>{
     flashed
 }
 
-fn flash(location: &Point, values: &mut Vec<Vec<i32>>) {
-    for neighbour in get_neighbours(*location) {
-        increase(&neighbour, values);    
-    }
-}
-
 fn all_cells<'a>(width: usize, height: usize) -> impl Iterator<Item = Point> + 'a {
     (0..width).flat_map(move |x| 
         (0..height).map(move |y| 
@@ -120,7 +182,7 @@ fn get_neighbours(location: Point) -> impl Iterator<Item = Point> {
     ).filter(move |l| *l != location)
 }
 
-fn increase(location: &Point, values: &mut Vec<Vec<i32>>) {
+fn increase(location: &Point, values: &mut [Vec<i32>]) {
     if !in_range(location, values) {
         return;
     }
@@ -157,8 +219,58 @@ mod tests {
     fn part2_should_work() {
         let input = fs::read_to_string("input.txt").unwrap();
         let values = parse_input(&input);
-        let result = part2(&values);
+        let result = part2(&values, MAX_CYCLE_STEPS);
+
+        assert_eq!(Ok(249), result);
+    }
+
+    #[test]
+    fn part2_should_fail_when_the_grid_never_synchronises() {
+        let input = fs::read_to_string("input4.txt").unwrap();
+        let values = parse_input(&input);
+        let result = part2(&values, MAX_CYCLE_STEPS);
+
+        assert_eq!(Err(String::from("The octopuses never flash at the same time, the grid repeats every 28 steps from step 76")), result);
+        assert!(cycle::find_cycle(&values, 103).is_none());
+        assert!(cycle::find_cycle(&values, 104).is_some());
+        assert_eq!(Err(String::from("No cycle within 50 steps")), part2(&values, 50));
+    }
+
+    #[test]
+    fn step_should_flash_each_cell_once() {
+        let input = fs::read_to_string("input3.txt").unwrap();
+        let mut values = parse_input(&input);
+
+        assert_eq!(9, simulate_step(&mut values).len());
+        assert_eq!(parse_input("34543\n40004\n50005\n40004\n34543"), values);
+    }
+
+    #[test]
+    fn cycle_should_predict_flashes() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let history = cycle::find_cycle(&values, MAX_CYCLE_STEPS).unwrap();
+
+        assert_eq!(cycle::Cycle { start: 195, period: 10, flashes_per_cycle: 100 }, history.cycle);
+        assert_eq!(1656, history.flashes_after(100));
+        assert_eq!(part1(&values, 300) as u64, history.flashes_after(300));
+        assert_eq!(history.flashes_after(195) + 100_000_000 * 100, history.flashes_after(1_000_000_195));
+    }
+
+    #[test]
+    fn animate_should_render_every_step() {
+        let input = fs::read_to_string("input3.txt").unwrap();
+        let values = parse_input(&input);
+        let mut frames = Vec::new();
+
+        animate(&values, 2, |step, values, flashed| {
+            frames.push((render::render_terminal(values, flashed, step), render::render_ppm(values, flashed, 1)));
+        });
 
-        assert_eq!(249, result);
+        assert_eq!(3, frames.len());
+        assert!(frames[1].0.starts_with("\x1b[HStep 1 - 9 flashes"));
+        assert_eq!(9, frames[1].0.matches("\x1b[1;30;103m").count());
+        assert_eq!(0, frames[2].0.matches("\x1b[1;30;103m").count());
+        assert_eq!(11 + 5 * 5 * 3, frames[0].1.len());
     }
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+use std::fmt::Write;
+use crate::Point;
+
+const FLASH: [u8; 3] = [255, 240, 120];
+
+// Energy levels from dark blue at 0 to light blue at 9
+fn colour(energy: i32) -> [u8; 3] {
+    let level = energy.clamp(0, 9) as u8;
+    [level * 12, level * 18, 60 + level * 20]
+}
+
+// Moves the cursor home before drawing, so consecutive frames overwrite each other
+pub fn render_terminal(values: &[Vec<i32>], flashed: &[Point], step: usize) -> String {
+    let mut output = String::from("\x1b[H");
+    writeln!(output, "Step {} - {} flashes\x1b[K", step, flashed.len()).unwrap();
+
+    for (y, row) in values.iter().enumerate() {
+        for (x, energy) in row.iter().enumerate() {
+            if flashed.contains(&Point::new(x as i32, y as i32)) {
+                output.push_str("\x1b[1;30;103m0\x1b[0m");
+            } else {
+                let [r, g, b] = colour(*energy);
+                write!(output, "\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, energy).unwrap();
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+pub fn render_ppm(values: &[Vec<i32>], flashed: &[Point], scale: usize) -> Vec<u8> {
+    let width = values[0].len();
+    let mut data = format!("P6\n{} {}\n255\n", width * scale, values.len() * scale).into_bytes();
+
+    for (y, row) in values.iter().enumerate() {
+        let pixels: Vec<[u8; 3]> = row.iter().enumerate()
+            .map(|(x, energy)| if flashed.contains(&Point::new(x as i32, y as i32)) { FLASH } else { colour(*energy) })
+            .collect();
+
+        for _ in 0..scale {
+            for pixel in pixels.iter() {
+                for _ in 0..scale {
+                    data.extend_from_slice(pixel);
+                }
+            }
+        }
+    }
+
+    data
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use crate::graph::Graph;
+use crate::policy::Policy;
+
+// (cave, packed visit counts, revisits used, moves left)
+type State = (usize, u64, usize, usize);
+
+// Where the visit count of a cave is stored in the packed counts
+#[derive(Clone, Copy)]
+struct Field {
+    shift: u32,
+    width: u32,
+}
+
+// Counts the paths from start to end without building them. The number of paths onwards from a
+// cave only depends on the visit counts of the limited caves, the revisits used and the moves left.
+pub fn count_paths(graph: &Graph, policy: &Policy) -> Result<u64, String> {
+    let start = graph.id(&policy.start).ok_or_else(|| format!("There is no start cave {}", policy.start))?;
+    let end = graph.id(&policy.end).ok_or_else(|| format!("There is no end cave {}", policy.end))?;
+    let allowed: Vec<bool> = graph.names.iter().map(|name| !policy.forbidden.contains(name)).collect();
+    let limits: Vec<Option<usize>> = graph.names.iter().enumerate()
+        .map(|(id, name)| policy.limit(name).or(if graph.small[id] { Some(1 + policy.revisits) } else { None }))
+        .collect();
+
+    if policy.max_length.is_none() {
+        check_finite(graph, &limits, &allowed)?;
+    }
+
+    let mut shift = 0;
+    let fields = limits.iter()
+        .map(|limit| limit.map(|limit| {
+            let width = usize::BITS - limit.leading_zeros();
+            shift += width;
+            Field { shift: shift - width, width }
+        }))
+        .collect();
+
+    if shift > u64::BITS {
+        return Err(format!("The visit counts need {} bits, but only 64 are available", shift));
+    }
+
+    let mut counter = Counter { graph, policy, start, end, allowed, fields, cache: HashMap::new() };
+
+    if !counter.allowed[start] || !counter.allowed[end] {
+        return Ok(0);
+    }
+
+    let visits = counter.visit(0, start);
+    Ok(counter.count((start, visits, 0, policy.max_length.unwrap_or(0))))
+}
+
+// Two connected caves that can both be visited without limit allow infinitely many paths
+fn check_finite(graph: &Graph, limits: &[Option<usize>], allowed: &[bool]) -> Result<(), String> {
+    let unlimited = |id: usize| limits[id].is_none() && allowed[id];
+
+    for (from, connections) in graph.connections.iter().enumerate().filter(|(from, _)| unlimited(*from)) {
+        if let Some(to) = connections.iter().find(|to| unlimited(**to)) {
+            return Err(format!("Caves {} and {} can be visited without limit, so there are infinitely many paths", graph.names[from], graph.names[*to]));
+        }
+    }
+
+    Ok(())
+}
+
+struct Counter<'a> {
+    graph: &'a Graph,
+    policy: &'a Policy,
+    start: usize,
+    end: usize,
+    allowed: Vec<bool>,
+    fields: Vec<Option<Field>>,
+    cache: HashMap<State, u64>,
+}
+
+impl<'a> Counter<'a> {
+    fn visits(&self, visits: u64, node: usize) -> usize {
+        match self.fields[node] {
+            Some(field) => ((visits >> field.shift) & ((1 << field.width) - 1)) as usize,
+            None => 0,
+        }
+    }
+
+    fn visit(&self, visits: u64, node: usize) -> u64 {
+        match self.fields[node] {
+            Some(field) => visits + (1 << field.shift),
+            None => visits,
+        }
+    }
+
+    // The revisits used after moving to the cave, or None when the cave can't be visited
+    fn revisits_after(&self, visits: u64, revisits_used: usize, node: usize) -> Option<usize> {
+        let count = self.visits(visits, node);
+
+        match self.policy.limit(&self.graph.names[node]) {
+            Some(limit) => if count < limit { Some(revisits_used) } else { None },
+            None if !self.graph.small[node] || count == 0 => Some(revisits_used),
+            None if revisits_used < self.policy.revisits => Some(revisits_used + 1),
+            None => None,
+        }
+    }
+
+    fn count(&mut self, state: State) -> u64 {
+        let (node, visits, revisits_used, moves_left) = state;
+
+        if node == self.end {
+            return 1;
+        }
+
+        if self.policy.max_length.is_some() && moves_left == 0 {
+            return 0;
+        }
+
+        if let Some(count) = self.cache.get(&state) {
+            return *count;
+        }
+
+        let moves_left = moves_left.saturating_sub(1);
+        let mut total = 0;
+
+        for next in self.graph.connections[node].iter().copied() {
+            if next == self.start || !self.allowed[next] {
+                continue;
+            }
+
+            if let Some(revisits_used) = self.revisits_after(visits, revisits_used, next) {
+                total += self.count((next, self.visit(visits, next), revisits_used, moves_left));
+            }
+        }
+
+        self.cache.insert(state, total);
+        total
+    }
+}
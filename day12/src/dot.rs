@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use itertools::Itertools;
+use crate::{is_small_cave, Node};
+
+// Writes the caves as an undirected Graphviz graph. Small caves are plain ellipses and large caves
+// filled boxes. The caves and connections of the highlighted path are drawn in red.
+pub fn to_dot(nodes: &HashMap<String, Node>, highlight: &[String]) -> Result<String, String> {
+    let steps: Vec<(&str, &str)> = highlight.iter().tuple_windows().map(|(a, b)| edge(a, b)).collect();
+
+    if let Some(name) = highlight.iter().find(|name| !nodes.contains_key(*name)) {
+        return Err(format!("The highlighted path visits unknown cave {}", name));
+    }
+    if let Some((a, b)) = steps.iter().find(|(a, b)| !nodes[*a].connections.iter().any(|c| c == b)) {
+        return Err(format!("The highlighted path uses {}-{}, but those caves aren't connected", a, b));
+    }
+
+    let mut output = String::from("graph caves {\n");
+
+    for name in nodes.keys().sorted() {
+        let style = if is_small_cave(name) { "shape=ellipse" } else { "shape=box, style=filled, fillcolor=lightblue" };
+        let colour = if highlight.contains(name) { ", color=red, penwidth=2" } else { "" };
+        writeln!(output, "    \"{}\" [{}{}];", name, style, colour).unwrap();
+    }
+
+    let edges = nodes.values()
+        .flat_map(|node| node.connections.iter().map(move |c| edge(&node.name, c)))
+        .unique()
+        .sorted();
+
+    for (a, b) in edges {
+        let colour = if steps.contains(&(a, b)) { " [color=red, penwidth=2]" } else { "" };
+        writeln!(output, "    \"{}\" -- \"{}\"{};", a, b, colour).unwrap();
+    }
+
+    output.push_str("}\n");
+    Ok(output)
+}
+
+fn edge<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b { (a, b) } else { (b, a) }
+}
@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+use crate::{is_small_cave, Node};
+
+// The cave system with every cave replaced by its index, so a search can work without strings
+pub struct Graph {
+    pub names: Vec<String>,
+    pub connections: Vec<Vec<usize>>,
+    pub small: Vec<bool>,
+}
+
+impl Graph {
+    pub fn new(nodes: &HashMap<String, Node>) -> Graph {
+        let mut names: Vec<String> = nodes.keys().cloned().collect();
+        names.sort();
+
+        let ids: HashMap<&str, usize> = names.iter().enumerate().map(|(id, name)| (name.as_str(), id)).collect();
+        let connections = names.iter()
+            .map(|name| nodes[name].connections.iter().map(|c| ids[c.as_str()]).collect())
+            .collect();
+        let small = names.iter().map(|name| is_small_cave(name)).collect();
+
+        Graph { names, connections, small }
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.names.binary_search_by(|n| n.as_str().cmp(name)).ok()
+    }
+}
@@ -1,5 +1,12 @@
-use std::{fs, collections::HashMap};
+use std::{env, fs, collections::HashMap};
 use itertools::Itertools;
+use graph::Graph;
+use policy::Policy;
+
+mod count;
+mod dot;
+mod graph;
+mod policy;
 
 #[derive(Debug)]
 struct Node {
@@ -8,19 +15,101 @@ struct Node {
 }
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
     let input = fs::read_to_string("input.txt").unwrap();
     let values = parse_input(&input);
-    
+
+    if args.iter().any(|arg| arg == "--enumerate") {
+        println!("Part 1: {} Part 2: {}", find_paths(&values, &Policy::single_visit()).len(), find_paths(&values, &Policy::one_revisit()).len());
+        return;
+    }
+
+    if let Some(path) = option_value(&args, "--dot") {
+        let highlight = option_value(&args, "--highlight").map_or(Vec::new(), |h| h.split(',').map(String::from).collect());
+
+        match dot::to_dot(&values, &highlight) {
+            Ok(dot) => fs::write(path, dot).unwrap(),
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
+
+    if args.iter().any(|arg| arg == "--list-paths") {
+        let policy = match policy_from_args(&args) {
+            Ok(policy) => policy.unwrap_or_else(Policy::single_visit),
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        };
+
+        for path in list_paths(&values, &policy) {
+            println!("{}", path);
+        }
+        return;
+    }
+
+    match policy_from_args(&args) {
+        Ok(Some(policy)) => {
+            match count::count_paths(&Graph::new(&values), &policy) {
+                Ok(count) => println!("Paths: {}", count),
+                Err(e) => println!("{}", e),
+            }
+            return;
+        }
+        Ok(None) => {},
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    }
+
     let part1 = part1(&values);
     let part2 = part2(&values);
 
     println!("Part 1: {} Part 2: {}", part1, part2);
 }
 
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).map(String::as_str)
+}
+
+// A policy based on the single visit preset, or None when no policy options were given
+fn policy_from_args(args: &[String]) -> Result<Option<Policy>, String> {
+    let flags = ["--revisits", "--limits", "--forbid", "--start", "--end", "--max-length"];
+    if !flags.iter().any(|flag| option_value(args, flag).is_some()) {
+        return Ok(None);
+    }
+
+    let mut policy = Policy::single_visit();
+
+    if let Some(revisits) = option_value(args, "--revisits") {
+        policy.revisits = revisits.parse().map_err(|_| format!("Invalid revisit count '{}'", revisits))?;
+    }
+    if let Some(limits) = option_value(args, "--limits") {
+        policy.limits = Policy::parse_limits(limits)?;
+    }
+    if let Some(forbidden) = option_value(args, "--forbid") {
+        policy.forbidden = forbidden.split(',').map(String::from).collect();
+    }
+    if let Some(start) = option_value(args, "--start") {
+        policy.start = String::from(start);
+    }
+    if let Some(end) = option_value(args, "--end") {
+        policy.end = String::from(end);
+    }
+    if let Some(max_length) = option_value(args, "--max-length") {
+        policy.max_length = Some(max_length.parse().map_err(|_| format!("Invalid path length '{}'", max_length))?);
+    }
+
+    Ok(Some(policy))
+}
+
 fn parse_input(input: &str) -> HashMap<String, Node> {
     let connections: Vec<Vec<String>> = input.lines().map(|line| line.split('-').map(String::from).collect_vec()).collect();
     let mut nodes: HashMap<String, Node> = connections.iter()
-        .flat_map(|c| c).unique()
+        .flatten().unique()
         .map(|name| (name.clone(), Node {name: name.clone(), connections: Vec::new()}))
         .collect();
     
@@ -37,49 +126,61 @@ fn connect_node(node1: &str, node2: &str, nodes: &mut HashMap<String, Node>) {
     node.connections.push(String::from(node2));
 }
 
-fn part1(nodes: &HashMap<String, Node>) -> usize {
-    find_paths(nodes, false).len()
+fn part1(nodes: &HashMap<String, Node>) -> u64 {
+    count::count_paths(&Graph::new(nodes), &Policy::single_visit()).unwrap()
 }
 
-fn part2(nodes: &HashMap<String, Node>) -> usize {
-    find_paths(nodes, true).len()
+fn part2(nodes: &HashMap<String, Node>) -> u64 {
+    count::count_paths(&Graph::new(nodes), &Policy::one_revisit()).unwrap()
 }
 
-fn find_paths(nodes: &HashMap<String, Node>, allow_multiple_visits: bool) -> Vec<Vec<String>> {
-    let mut path = vec![String::from("start")];
+fn find_paths(nodes: &HashMap<String, Node>, policy: &Policy) -> Vec<Vec<String>> {
+    let mut path = vec![policy.start.clone()];
     let mut paths = Vec::new();
 
-    search_node(nodes, &mut path, &mut paths, allow_multiple_visits);
+    if nodes.contains_key(&policy.start) && !policy.forbidden.contains(&policy.start) {
+        search_node(nodes, &mut path, &mut paths, policy);
+    }
 
     paths
 }
 
-fn search_node(nodes: &HashMap<String, Node>, path: &mut Vec<String>, paths: &mut Vec<Vec<String>>, allow_multiple_visits: bool) {
+fn list_paths(nodes: &HashMap<String, Node>, policy: &Policy) -> Vec<String> {
+    find_paths(nodes, policy).iter().map(|path| path.join(",")).sorted().collect()
+}
+
+fn search_node(nodes: &HashMap<String, Node>, path: &mut Vec<String>, paths: &mut Vec<Vec<String>>, policy: &Policy) {
     let node = nodes.get(path.last().unwrap()).unwrap();
-    if node.name == "end" {
+    if node.name == policy.end {
         paths.push(path.clone());
         return;
     }
 
     for next_node in node.connections.iter() {
-        if can_visit_node(&next_node, path, allow_multiple_visits) {
+        if can_visit_node(next_node, path, policy) {
             path.push(next_node.clone());
-            search_node(nodes, path, paths, allow_multiple_visits);
+            search_node(nodes, path, paths, policy);
             path.pop();
         }
     }
 }
 
-fn can_visit_node(name: &String, path: &Vec<String>, allow_multiple_visits: bool) -> bool {
-    if is_large_cave(name) || !path.contains(name) {
-        return true;
+fn can_visit_node(name: &str, path: &[String], policy: &Policy) -> bool {
+    if name == policy.start || policy.forbidden.contains(name) {
+        return false;
+    }
+
+    // The path holds one more cave than it has moves
+    if policy.max_length.is_some_and(|max| path.len() > max) {
+        return false;
     }
 
-    // We have a small cave that we have visited before
-    if allow_multiple_visits && !is_start_or_end(name) && !visited_small_cave_twice(path) {
-        true
-    } else {
-        false
+    let visits = path.iter().filter(|n| *n == name).count();
+
+    match policy.limit(name) {
+        Some(limit) => visits < limit,
+        None if is_large_cave(name) => true,
+        None => visits == 0 || revisits_used(path, policy) < policy.revisits,
     }
 }
 
@@ -91,14 +192,13 @@ fn is_small_cave(name: &str) -> bool {
     name.chars().all(|c| c.is_lowercase())
 }
 
-fn visited_small_cave_twice(path: &Vec<String>) -> bool {
+// The extra visits made to small caves that don't have their own limit
+fn revisits_used(path: &[String], policy: &Policy) -> usize {
     path.iter()
-        .filter(|n| is_small_cave(n)).counts().values()
-        .any(|count| *count > 1)
-}
-
-fn is_start_or_end(name: &str) -> bool {
-    name == "start" || name ==  "end"
+        .filter(|n| is_small_cave(n) && policy.limit(n).is_none())
+        .counts().values()
+        .map(|count| count - 1)
+        .sum()
 }
 
 #[cfg(test)]
@@ -122,4 +222,75 @@ mod tests {
 
         assert_eq!(36, result);
     }
+
+    #[test]
+    fn count_should_match_enumerated_paths() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let graph = Graph::new(&values);
+
+        assert_eq!(find_paths(&values, &Policy::single_visit()).len() as u64, count::count_paths(&graph, &Policy::single_visit()).unwrap());
+        assert_eq!(find_paths(&values, &Policy::one_revisit()).len() as u64, count::count_paths(&graph, &Policy::one_revisit()).unwrap());
+    }
+
+    #[test]
+    fn count_should_reject_connected_large_caves() {
+        let values = parse_input("start-A\nA-B\nB-end");
+
+        let mut policy = Policy::single_visit();
+
+        assert!(count::count_paths(&Graph::new(&values), &policy).is_err());
+
+        policy.max_length = Some(5);
+        assert_eq!(2, count::count_paths(&Graph::new(&values), &policy).unwrap());
+    }
+
+    #[test]
+    fn policies_should_match_enumerated_paths() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let graph = Graph::new(&values);
+
+        let mut policies = vec![Policy { revisits: 2, ..Policy::single_visit() }];
+        policies.push(Policy { limits: Policy::parse_limits("b=3,A=2").unwrap(), ..Policy::one_revisit() });
+        policies.push(Policy { forbidden: ["b"].iter().map(|n| n.to_string()).collect(), ..Policy::one_revisit() });
+        policies.push(Policy { start: String::from("c"), end: String::from("d"), ..Policy::one_revisit() });
+        policies.push(Policy { max_length: Some(4), ..Policy::one_revisit() });
+
+        for policy in policies {
+            let paths = find_paths(&values, &policy);
+
+            assert!(!paths.is_empty());
+            assert_eq!(paths.len() as u64, count::count_paths(&graph, &policy).unwrap(), "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn list_paths_should_be_sorted() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let paths = list_paths(&values, &Policy::single_visit());
+
+        assert_eq!(10, paths.len());
+        assert_eq!("start,A,b,A,c,A,end", paths[0]);
+        assert_eq!("start,b,end", paths[9]);
+    }
+
+    #[test]
+    fn dot_should_style_caves_and_highlight_a_path() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let values = parse_input(&input);
+        let path = ["start", "A", "b", "end"].iter().map(|n| n.to_string()).collect_vec();
+        let dot = dot::to_dot(&values, &path).unwrap();
+
+        assert!(dot.starts_with("graph caves {\n"));
+        assert!(dot.contains("\"A\" [shape=box, style=filled, fillcolor=lightblue, color=red, penwidth=2];"));
+        assert!(dot.contains("\"c\" [shape=ellipse];"));
+        assert!(dot.contains("\"A\" -- \"b\" [color=red, penwidth=2];"));
+        assert!(dot.contains("\"A\" -- \"c\";"));
+        assert_eq!(7, dot.matches(" -- ").count());
+
+        let invalid = ["start", "c"].iter().map(|n| n.to_string()).collect_vec();
+        assert!(dot::to_dot(&values, &invalid).is_err());
+    }
 }
\ No newline at end of file
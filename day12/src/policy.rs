@@ -0,0 +1,48 @@
+use std::collections::{HashMap, HashSet};
+
+// Decides which caves a path may (re)visit. Small caves can be visited once, plus a shared budget
+// of extra visits. Caves with an explicit limit ignore that budget, and large caves without a
+// limit can be visited any number of times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    pub start: String,
+    pub end: String,
+    pub revisits: usize,
+    pub limits: HashMap<String, usize>,
+    pub forbidden: HashSet<String>,
+    // The maximum number of moves in a path
+    pub max_length: Option<usize>,
+}
+
+impl Policy {
+    pub fn single_visit() -> Policy {
+        Policy {
+            start: String::from("start"),
+            end: String::from("end"),
+            revisits: 0,
+            limits: HashMap::new(),
+            forbidden: HashSet::new(),
+            max_length: None,
+        }
+    }
+
+    pub fn one_revisit() -> Policy {
+        Policy { revisits: 1, ..Policy::single_visit() }
+    }
+
+    pub fn limit(&self, name: &str) -> Option<usize> {
+        self.limits.get(name).copied()
+    }
+
+    // Parses a comma separated list of "<cave>=<limit>" entries
+    pub fn parse_limits(spec: &str) -> Result<HashMap<String, usize>, String> {
+        spec.split(',')
+            .map(|entry| {
+                let (name, limit) = entry.split_once('=').ok_or_else(|| format!("Expected <cave>=<limit>, found '{}'", entry))?;
+                let limit = limit.parse().map_err(|_| format!("Invalid limit '{}' for cave {}", limit, name))?;
+
+                Ok((String::from(name), limit))
+            })
+            .collect()
+    }
+}
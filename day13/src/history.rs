@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+use crate::{fold, fold_coordinate, render_points, Fold, Input, Point};
+
+pub struct FoldStep {
+    pub fold: Fold,
+    pub points: HashSet<Point>,
+    // Each dot that was covered by a dot from the folded half, with the dot that covered it
+    pub merged: Vec<(Point, Point)>,
+    pub render: String,
+}
+
+impl FoldStep {
+    pub fn dots(&self) -> usize {
+        self.points.len()
+    }
+}
+
+pub fn fold_history(input: &Input) -> Vec<FoldStep> {
+    let mut points: HashSet<Point> = input.points.iter().copied().collect();
+    let mut steps = Vec::new();
+
+    for f in input.folds.iter().copied() {
+        let mut merged: Vec<(Point, Point)> = points.iter()
+            .filter(|p| is_folded(**p, f))
+            .map(|p| (mirror(*p, f), *p))
+            .filter(|(target, _)| points.contains(target))
+            .collect();
+        merged.sort_by_key(|(target, _)| (target.y, target.x));
+
+        points = fold(&points, f);
+        steps.push(FoldStep { fold: f, render: render_points(&points), points: points.clone(), merged });
+    }
+
+    steps
+}
+
+fn is_folded(point: Point, f: Fold) -> bool {
+    match f {
+        Fold::X(x) => point.x > x,
+        Fold::Y(y) => point.y > y,
+    }
+}
+
+fn mirror(point: Point, f: Fold) -> Point {
+    match f {
+        Fold::X(x) => Point::new(fold_coordinate(point.x, x), point.y),
+        Fold::Y(y) => Point::new(point.x, fold_coordinate(point.y, y)),
+    }
+}
+
+// The sheets that fold into a set of dots. Merged dots are known to have been on both halves,
+// every other dot was on exactly one of them, so there are 2^choices candidates.
+pub struct Unfold {
+    pub fixed: HashSet<Point>,
+    pub choices: Vec<(Point, Point)>,
+}
+
+pub fn unfold(points: &HashSet<Point>, f: Fold, merged: &[(Point, Point)]) -> Unfold {
+    let fixed: HashSet<Point> = merged.iter().flat_map(|(a, b)| [*a, *b]).collect();
+    let mut choices: Vec<(Point, Point)> = points.iter()
+        .filter(|p| !fixed.contains(p))
+        .map(|p| (*p, mirror(*p, f)))
+        .collect();
+    choices.sort_by_key(|(p, _)| (p.y, p.x));
+
+    Unfold { fixed, choices }
+}
+
+impl Unfold {
+    // Lists every candidate, starting with all undecided dots on the unfolded half
+    pub fn candidates(&self) -> impl Iterator<Item = HashSet<Point>> + '_ {
+        let mut mirrored = vec![false; self.choices.len()];
+        let mut done = false;
+
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+
+            let candidate = self.fixed.iter().copied()
+                .chain(self.choices.iter().zip(mirrored.iter()).map(|((p, m), flip)| if *flip { *m } else { *p }))
+                .collect();
+
+            done = true;
+            for flip in mirrored.iter_mut() {
+                *flip = !*flip;
+                if *flip {
+                    done = false;
+                    break;
+                }
+            }
+
+            Some(candidate)
+        })
+    }
+}
@@ -1,7 +1,10 @@
 use core::panic;
-use std::{fs, collections::HashSet};
+use std::{env, fmt, fs, collections::HashSet};
 use itertools::Itertools;
 
+mod history;
+mod ocr;
+
 #[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
 struct Point {
     x: i32,
@@ -20,6 +23,15 @@ enum Fold {
     Y(i32),
 }
 
+impl fmt::Display for Fold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fold::X(x) => write!(f, "x={}", x),
+            Fold::Y(y) => write!(f, "y={}", y),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Input {
     points: Vec<Point>,
@@ -27,18 +39,47 @@ struct Input {
 }
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
     let input = fs::read_to_string("input.txt").unwrap();
     let input = parse_input(&input);
 
+    if args.iter().any(|arg| arg == "--steps") {
+        for step in history::fold_history(&input) {
+            println!("Fold along {}: {} dots, {} merged", step.fold, step.dots(), step.merged.len());
+            println!("{}", step.render);
+        }
+        return;
+    }
+
+    if let Some(index) = option_value(&args, "--unfold") {
+        let history = history::fold_history(&input);
+        let step = &history[index.parse::<usize>().unwrap() - 1];
+        let unfold = history::unfold(&step.points, step.fold, &step.merged);
+
+        println!("The sheet after folding along {} can be unfolded in 2^{} ways, for example:", step.fold, unfold.choices.len());
+        println!("{}", render_points(&unfold.candidates().next().unwrap()));
+        return;
+    }
+
     let part1 = part1(&input);
     let part2 = part2(&input);
 
     println!("Part 1: {}", part1);
-    println!("Part 2:\n{}", part2);
+
+    match part2 {
+        Ok(text) => println!("Part 2: {}", text),
+        Err(e) => println!("Part 2: {}\n{}", e, render_points(&fold_all(&input))),
+    }
+}
+
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1).map(String::as_str)
 }
 
 fn parse_input(input: &str) -> Input {
-    let parts = input.split_once("\r\n\r\n").unwrap();
+    let input = input.replace("\r\n", "\n");
+    let parts = input.split_once("\n\n").unwrap();
 
     Input { 
         points: parse_points(parts.0),
@@ -94,14 +135,16 @@ fn part1(input: &Input) -> usize {
     points.len()
 }
 
-fn part2(input: &Input) -> String {
-    let points = input.folds.iter()
+fn part2(input: &Input) -> Result<String, String> {
+    ocr::read_text(&render_points(&fold_all(input)))
+}
+
+fn fold_all(input: &Input) -> HashSet<Point> {
+    input.folds.iter()
         .fold(
             input.points.iter().copied().collect(),
             |points, f| fold(&points, *f)
-        );
-    
-    render_points(&points)
+        )
 }
 
 fn render_points(points: &HashSet<Point>) -> String {
@@ -111,7 +154,7 @@ fn render_points(points: &HashSet<Point>) -> String {
 
     for y in 0..=max_y {
         for x in 0..=max_x {
-            let point = points.contains(&Point::new(x as i32, y as i32));
+            let point = points.contains(&Point::new(x, y));
 
             if point {
                 chars.push('#');
@@ -145,6 +188,15 @@ mod tests {
         let input = parse_input(&input);
         let result = part2(&input);
 
+        assert_eq!(Ok(String::from("EFJKZLBL")), result);
+    }
+
+    #[test]
+    fn render_should_draw_the_folded_paper() {
+        let input = fs::read_to_string("input.txt").unwrap();
+        let input = parse_input(&input);
+        let result = render_points(&fold_all(&input));
+
         assert_eq!("####.####...##.#..#.####.#....###..#...
 #....#.......#.#.#.....#.#....#..#.#...
 ###..###.....#.##.....#..#....###..#...
@@ -153,4 +205,46 @@ mod tests {
 ####.#.....##..#..#.####.####.###..####
 ", result);
     }
-}
+
+    #[test]
+    fn ocr_should_report_unknown_glyphs() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let input = parse_input(&input);
+        let art = render_points(&fold_all(&input));
+
+        assert_eq!(Err(String::from("Expected 6 rows of letters, found 5")), ocr::read_text(&art));
+        assert_eq!(Ok(String::from("HI")), ocr::read_text("#..#..###\n#..#...#.\n####...#.\n#..#...#.\n#..#...#.\n#..#..###"));
+        assert_eq!(Err(String::from("Unrecognised glyph at position 2:\n####\n#..#\n#..#\n#..#\n#..#\n####")),
+            ocr::read_text("#....####\n#....#..#\n#....#..#\n#....#..#\n#....#..#\n####.####"));
+    }
+
+    #[test]
+    fn history_should_record_each_fold() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let input = parse_input(&input);
+        let history = history::fold_history(&input);
+
+        assert_eq!(vec![17, 16], history.iter().map(|step| step.dots()).collect_vec());
+        assert_eq!(18 - 17, history[0].merged.len());
+        assert_eq!(17 - 16, history[1].merged.len());
+        assert_eq!(render_points(&fold_all(&input)), history[1].render);
+    }
+
+    #[test]
+    fn unfold_should_include_the_original_sheet() {
+        let input = fs::read_to_string("input2.txt").unwrap();
+        let input = parse_input(&input);
+        let history = history::fold_history(&input);
+        let mut before: HashSet<Point> = input.points.iter().copied().collect();
+
+        for step in history.iter() {
+            let unfold = history::unfold(&step.points, step.fold, &step.merged);
+
+            assert_eq!(step.dots() - step.merged.len(), unfold.choices.len());
+            assert!(unfold.candidates().take(100).all(|c| fold(&c, step.fold) == step.points));
+            assert!(unfold.candidates().any(|c| c == before));
+
+            before = step.points.clone();
+        }
+    }
+}
\ No newline at end of file
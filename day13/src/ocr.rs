@@ -0,0 +1,57 @@
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+
+// The letters of the 4x6 font used by the puzzles. Each glyph is followed by an empty column.
+const FONT: [(char, &str); 17] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+
+// Reads the letters in art made of '#' and '.', like the output of render_points. Glyphs at the
+// right edge may be cut short, so missing columns are treated as empty.
+pub fn read_text(art: &str) -> Result<String, String> {
+    let rows: Vec<Vec<char>> = art.lines().map(|line| line.chars().collect()).collect();
+
+    if rows.len() != GLYPH_HEIGHT {
+        return Err(format!("Expected {} rows of letters, found {}", GLYPH_HEIGHT, rows.len()));
+    }
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    (0..width).step_by(GLYPH_WIDTH + 1)
+        .enumerate()
+        .map(|(index, left)| {
+            let glyph: String = rows.iter()
+                .flat_map(|row| (left..left + GLYPH_WIDTH).map(move |x| row.get(x).copied().unwrap_or('.')))
+                .collect();
+
+            FONT.iter()
+                .find(|(_, pattern)| *pattern == glyph)
+                .map(|(letter, _)| *letter)
+                .ok_or_else(|| format!("Unrecognised glyph at position {}:\n{}", index + 1, format_glyph(&glyph)))
+        })
+        .collect()
+}
+
+fn format_glyph(glyph: &str) -> String {
+    glyph.chars().collect::<Vec<char>>()
+        .chunks(GLYPH_WIDTH)
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}